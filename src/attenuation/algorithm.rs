@@ -0,0 +1,237 @@
+//! Interchangeable attenuation solvers behind one interface.
+//!
+//! [`AttenuationAlgorithm`] is object safe, so viewers, benchmarks and tests can
+//! hold a `Box<dyn AttenuationAlgorithm>` and switch solvers at runtime:
+//! - [`Sweeping`] – bidirectional sweeps (fast, bends around corners)
+//! - [`FloodFill`] – breadth-first flood fill (each cell lit via its fewest-step paths)
+//! - [`Raycast`] – straight rays from each source (hard shadows, no bending)
+//! - [`BestPath`] – exact best-path search (slow ground truth)
+
+use std::collections::VecDeque;
+
+use super::model::{DecayModel, Linear};
+use super::reference::BestPath;
+use super::region::Region;
+use super::shape::line_cells;
+use super::sweeping::{DEFAULT_EPSILON, Kernel, Sweeping};
+
+/// A solver that turns a decay grid and light sources into attenuation.
+pub trait AttenuationAlgorithm: Send + Sync {
+    /// Short human-readable name, for benchmarks and viewers.
+    fn name(&self) -> &'static str;
+
+    /// Write the combined attenuation of `(x, y, intensity)` sources into `out`
+    /// (row-major: index = y * width + x, `out.len() == width * height`).
+    fn calculate_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        sources: &[(usize, usize, f32)],
+        out: &mut [f32],
+    );
+
+    /// Allocating convenience wrapper around [`calculate_into`](Self::calculate_into).
+    fn calculate(&self, decay_flat: &[f32], width: usize, height: usize, sources: &[(usize, usize, f32)]) -> Vec<f32> {
+        let mut out = vec![0.0f32; width * height];
+        self.calculate_into(decay_flat, width, height, sources, &mut out);
+        out
+    }
+}
+
+impl<M: DecayModel> AttenuationAlgorithm for Sweeping<M> {
+    fn name(&self) -> &'static str {
+        "sweeping"
+    }
+
+    fn calculate_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        sources: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        self.calculate_many_into(decay_flat, width, height, sources, out);
+    }
+}
+
+impl<M: DecayModel> AttenuationAlgorithm for BestPath<M> {
+    fn name(&self) -> &'static str {
+        "best path"
+    }
+
+    fn calculate_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        sources: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        self.calculate_many_into(decay_flat, width, height, sources, out);
+    }
+}
+
+/// Breadth-first flood fill, the classic tile-lighting approach.
+///
+/// Cells are reached layer by layer in order of step count, and each takes the
+/// brightest light offered by the previous layer. Light therefore follows the
+/// paths with the fewest steps, which may not be the brightest ones.
+#[derive(Debug, Clone)]
+pub struct FloodFill<M: DecayModel = Linear> {
+    /// Multiplier for diagonal distance (default: √2 ≈ 1.414)
+    pub diagonal_decay_mult: f32,
+    /// Light below this level is not spread further (default: 1e-4)
+    pub epsilon: f32,
+    /// Neighbourhood used for propagation (default: [`Kernel::Eight`]; knight
+    /// moves are not supported, so [`Kernel::Sixteen`] acts like `Eight`)
+    pub kernel: Kernel,
+    /// Per-step transfer function (see [`DecayModel`])
+    pub model: M,
+}
+
+impl<M: DecayModel + Default> Default for FloodFill<M> {
+    fn default() -> Self {
+        FloodFill {
+            diagonal_decay_mult: std::f32::consts::SQRT_2,
+            epsilon: DEFAULT_EPSILON,
+            kernel: Kernel::Eight,
+            model: M::default(),
+        }
+    }
+}
+
+impl FloodFill {
+    /// Create a flood fill with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: DecayModel> AttenuationAlgorithm for FloodFill<M> {
+    fn name(&self) -> &'static str {
+        "flood fill"
+    }
+
+    fn calculate_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        sources: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        out.fill(0.0);
+        let kernel = if self.kernel == Kernel::Sixteen { Kernel::Eight } else { self.kernel };
+        let steps = kernel.steps(self.diagonal_decay_mult);
+        let mut layer = vec![u32::MAX; width * height];
+        let mut queue = VecDeque::new();
+
+        for &(x, y, intensity) in sources {
+            let idx = y * width + x;
+            out[idx] = out[idx].max(intensity);
+            if layer[idx] == u32::MAX {
+                layer[idx] = 0;
+                queue.push_back(idx);
+            }
+        }
+
+        while let Some(idx) = queue.pop_front() {
+            let light = out[idx];
+            if light < self.epsilon || light <= 0.0 {
+                continue;
+            }
+            let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+            for &(dx, dy, mult) in &steps {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+                let ni = ny as usize * width + nx as usize;
+                // Only cells in the next layer can still take light from this one
+                if layer[ni] <= layer[idx] {
+                    continue;
+                }
+                if layer[ni] == u32::MAX {
+                    layer[ni] = layer[idx] + 1;
+                    queue.push_back(ni);
+                }
+                out[ni] = out[ni].max(self.model.transmit(light, decay_flat[idx], mult));
+            }
+        }
+    }
+}
+
+/// Straight-line raycasting from every source to every cell in range.
+///
+/// Light only travels along the Bresenham line to each cell, charging each cell
+/// it leaves, so shadows are hard and light never bends around corners. Cost is
+/// proportional to the lit area times the light's range.
+#[derive(Debug, Clone)]
+pub struct Raycast<M: DecayModel = Linear> {
+    /// Multiplier for diagonal distance (default: √2 ≈ 1.414)
+    pub diagonal_decay_mult: f32,
+    /// Cells are only traced within the range where light can exceed this (default: 1e-4)
+    pub epsilon: f32,
+    /// Per-step transfer function (see [`DecayModel`])
+    pub model: M,
+}
+
+impl<M: DecayModel + Default> Default for Raycast<M> {
+    fn default() -> Self {
+        Raycast {
+            diagonal_decay_mult: std::f32::consts::SQRT_2,
+            epsilon: DEFAULT_EPSILON,
+            model: M::default(),
+        }
+    }
+}
+
+impl Raycast {
+    /// Create a raycaster with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: DecayModel> AttenuationAlgorithm for Raycast<M> {
+    fn name(&self) -> &'static str {
+        "raycast"
+    }
+
+    fn calculate_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        sources: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        out.fill(0.0);
+        let min_decay = decay_flat.iter().copied().fold(f32::INFINITY, f32::min);
+
+        for &(sx, sy, intensity) in sources {
+            let Some(radius) = self.model.reach(intensity, min_decay, self.epsilon) else {
+                continue;
+            };
+            let window = Region::around(sx, sy, radius, width, height);
+            for ty in window.y0..window.y1 {
+                for tx in window.x0..window.x1 {
+                    let ray = line_cells((sx, sy), (tx, ty));
+                    let mut light = intensity;
+                    for pair in ray.windows(2) {
+                        let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+                        let mult = if ax != bx && ay != by { self.diagonal_decay_mult } else { 1.0 };
+                        light = self.model.transmit(light, decay_flat[ay * width + ax], mult);
+                        if light <= 0.0 {
+                            break;
+                        }
+                    }
+                    let idx = ty * width + tx;
+                    out[idx] = out[idx].max(light);
+                }
+            }
+        }
+    }
+}
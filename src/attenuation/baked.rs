@@ -0,0 +1,183 @@
+//! Baked static lighting with dynamic lights composited on top.
+//!
+//! Most lights in a scene (torches, lava) never move. A [`BakedLighting`] sweeps
+//! them once into a baked light map and keeps it until the static lights or the
+//! decay grid change; each frame only the dynamic lights are swept and combined
+//! with the map.
+//!
+//! With [`Combine::Max`] the composite equals [`Sweeping::calculate_many`] over
+//! the static and dynamic lights together within the sweep's `epsilon`: a sweep
+//! of several seeds is the max of the sweeps of each seed, but the static and
+//! dynamic lights are clipped to their own [light windows](Sweeping::light_window),
+//! so light that leaves one below `epsilon` and would come back is dropped. With
+//! [`Combine::Additive`] every light is swept on its own and the results are
+//! summed, like [`blend_lights`](crate::blend_lights) does for colours.
+
+use super::model::{DecayModel, Linear};
+use super::sweeping::Sweeping;
+use super::workspace::SweepWorkspace;
+
+/// How the contributions of several lights are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Combine {
+    /// Brightest light wins (one sweep for all lights, never over-saturates)
+    #[default]
+    Max,
+    /// Contributions are summed (one sweep per light)
+    Additive,
+}
+
+/// A decay grid with a baked light map of its static lights.
+#[derive(Debug, Clone)]
+pub struct BakedLighting<M: DecayModel = Linear> {
+    workspace: SweepWorkspace<M>,
+    combine: Combine,
+    width: usize,
+    height: usize,
+    decay: Vec<f32>,
+    static_lights: Vec<(usize, usize, f32)>,
+    baked: Vec<f32>,
+    /// Whether `baked` matches the current decay grid and static lights
+    valid: bool,
+    /// One light's attenuation when combining additively
+    scratch: Vec<f32>,
+}
+
+impl<M: DecayModel> BakedLighting<M> {
+    /// Create an unbaked light map for `decay_flat` with no static lights.
+    pub fn new(sweeping: Sweeping<M>, combine: Combine, decay_flat: &[f32], width: usize, height: usize) -> Self {
+        assert_eq!(decay_flat.len(), width * height, "decay grid does not match {width}x{height}");
+        BakedLighting {
+            workspace: SweepWorkspace::with_capacity(sweeping, width, height),
+            combine,
+            width,
+            height,
+            decay: decay_flat.to_vec(),
+            static_lights: Vec::new(),
+            baked: vec![0.0; width * height],
+            valid: false,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Grid width in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Grid height in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The decay grid the map is baked for.
+    pub fn decay(&self) -> &[f32] {
+        &self.decay
+    }
+
+    /// How lights are combined.
+    pub fn combine(&self) -> Combine {
+        self.combine
+    }
+
+    /// The `(x, y, intensity)` lights in the baked map.
+    pub fn static_lights(&self) -> &[(usize, usize, f32)] {
+        &self.static_lights
+    }
+
+    /// Whether the baked map is up to date, so compositing only sweeps the
+    /// dynamic lights.
+    pub fn is_baked(&self) -> bool {
+        self.valid
+    }
+
+    /// Replace the static lights. Invalidates the map if they differ.
+    pub fn set_static_lights(&mut self, lights: &[(usize, usize, f32)]) {
+        if self.static_lights != lights {
+            self.static_lights.clear();
+            self.static_lights.extend_from_slice(lights);
+            self.valid = false;
+        }
+    }
+
+    /// Set the decay of cell `(x, y)`. Invalidates the map if it changes.
+    pub fn set_decay(&mut self, x: usize, y: usize, decay: f32) {
+        let cell = &mut self.decay[y * self.width + x];
+        if *cell != decay {
+            *cell = decay;
+            self.valid = false;
+        }
+    }
+
+    /// Replace the whole decay grid (same size). Invalidates the map if any
+    /// cell differs. Compares every cell, so call it on edits rather than per
+    /// frame, and prefer [`set_decay`](Self::set_decay) for single cells.
+    pub fn set_decay_grid(&mut self, decay_flat: &[f32]) {
+        assert_eq!(decay_flat.len(), self.width * self.height, "decay grid size changed");
+        if self.decay != decay_flat {
+            self.decay.copy_from_slice(decay_flat);
+            self.valid = false;
+        }
+    }
+
+    /// The light of the static lights alone, baking it first if it is stale.
+    pub fn bake(&mut self) -> &[f32] {
+        if !self.valid {
+            let (mut baked, lights) = (std::mem::take(&mut self.baked), std::mem::take(&mut self.static_lights));
+            self.sweep_into(&lights, &mut baked);
+            (self.baked, self.static_lights) = (baked, lights);
+            self.valid = true;
+        }
+        &self.baked
+    }
+
+    /// Write the baked static light combined with `dynamic` `(x, y, intensity)`
+    /// lights into `out` (`out.len() == width * height`), baking first if the
+    /// map is stale.
+    pub fn composite_into(&mut self, dynamic: &[(usize, usize, f32)], out: &mut [f32]) {
+        self.bake();
+        if dynamic.is_empty() {
+            out.copy_from_slice(&self.baked);
+            return;
+        }
+        match self.combine {
+            Combine::Max => {
+                self.workspace.calculate_into(&self.decay, self.width, self.height, dynamic, out);
+                for (o, &b) in out.iter_mut().zip(&self.baked) {
+                    *o = o.max(b);
+                }
+            }
+            Combine::Additive => {
+                self.sweep_into(dynamic, out);
+                for (o, &b) in out.iter_mut().zip(&self.baked) {
+                    *o += b;
+                }
+            }
+        }
+    }
+
+    /// [`composite_into`](Self::composite_into) into a new grid.
+    pub fn composite(&mut self, dynamic: &[(usize, usize, f32)]) -> Vec<f32> {
+        let mut out = vec![0.0; self.width * self.height];
+        self.composite_into(dynamic, &mut out);
+        out
+    }
+
+    /// Combined attenuation of `lights` on their own
+    fn sweep_into(&mut self, lights: &[(usize, usize, f32)], out: &mut [f32]) {
+        let (width, height) = (self.width, self.height);
+        match self.combine {
+            Combine::Max => self.workspace.calculate_into(&self.decay, width, height, lights, out),
+            Combine::Additive => {
+                out.fill(0.0);
+                self.scratch.resize(width * height, 0.0);
+                for light in lights {
+                    self.workspace.calculate_into(&self.decay, width, height, std::slice::from_ref(light), &mut self.scratch);
+                    for (o, &s) in out.iter_mut().zip(&self.scratch) {
+                        *o += s;
+                    }
+                }
+            }
+        }
+    }
+}
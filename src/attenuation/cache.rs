@@ -0,0 +1,190 @@
+//! LRU cache of single-light attenuation grids.
+//!
+//! A light that jitters between a handful of cells (a mouse light, the four
+//! corners of a subpixel blend) keeps asking for the same grids. An
+//! [`AttenuationCache`] owns the decay grid and keeps recent results keyed by
+//! `(x, y, intensity, revision)`, where the revision is bumped by every decay
+//! edit, so a cached grid can never be served for a world it was not swept in.
+//! Grids are evicted least recently used first once they exceed a memory budget.
+
+use std::collections::HashMap;
+
+use super::model::{DecayModel, Linear};
+use super::sweeping::Sweeping;
+use super::workspace::SweepWorkspace;
+
+/// Lookup counts of an [`AttenuationCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CacheStats {
+    /// Lookups served from the cache
+    pub hits: u64,
+    /// Lookups that had to sweep
+    pub misses: u64,
+    /// Grids dropped to stay within the memory budget
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache (0 before the first lookup).
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 { 0.0 } else { self.hits as f64 / lookups as f64 }
+    }
+}
+
+/// `(x, y, intensity bits, decay revision)`
+type Key = (usize, usize, u32, u64);
+
+#[derive(Debug, Clone)]
+struct Entry {
+    grid: Vec<f32>,
+    /// Lookup clock of the last hit, for LRU eviction
+    last_used: u64,
+}
+
+/// A decay grid with a memory-bounded LRU cache of single-light sweeps.
+#[derive(Debug, Clone)]
+pub struct AttenuationCache<M: DecayModel = Linear> {
+    workspace: SweepWorkspace<M>,
+    width: usize,
+    height: usize,
+    decay: Vec<f32>,
+    revision: u64,
+    budget_bytes: usize,
+    entries: HashMap<Key, Entry>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<M: DecayModel> AttenuationCache<M> {
+    /// Create an empty cache for `decay_flat` holding at most `budget_bytes` of
+    /// grids. It always keeps the most recent grid, even over budget.
+    pub fn new(sweeping: Sweeping<M>, decay_flat: &[f32], width: usize, height: usize, budget_bytes: usize) -> Self {
+        assert_eq!(decay_flat.len(), width * height, "decay grid does not match {width}x{height}");
+        AttenuationCache {
+            workspace: SweepWorkspace::with_capacity(sweeping, width, height),
+            width,
+            height,
+            decay: decay_flat.to_vec(),
+            revision: 0,
+            budget_bytes,
+            entries: HashMap::new(),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Grid width in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Grid height in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The decay grid lights are swept through.
+    pub fn decay(&self) -> &[f32] {
+        &self.decay
+    }
+
+    /// Revision of the decay grid, bumped by every edit that changes it.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Number of cached grids.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no grid is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Bytes held by cached grids.
+    pub fn memory_bytes(&self) -> usize {
+        self.entries.len() * self.grid_bytes()
+    }
+
+    /// Hit, miss and eviction counts since creation.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Set the decay of cell `(x, y)`, bumping the revision if it changes.
+    pub fn set_decay(&mut self, x: usize, y: usize, decay: f32) {
+        let cell = &mut self.decay[y * self.width + x];
+        if *cell != decay {
+            *cell = decay;
+            self.bump_revision();
+        }
+    }
+
+    /// Replace the whole decay grid (same size), bumping the revision if any
+    /// cell differs. Compares every cell, so call it on edits rather than per
+    /// frame, and prefer [`set_decay`](Self::set_decay) for single cells.
+    pub fn set_decay_grid(&mut self, decay_flat: &[f32]) {
+        assert_eq!(decay_flat.len(), self.width * self.height, "decay grid size changed");
+        if self.decay != decay_flat {
+            self.decay.copy_from_slice(decay_flat);
+            self.bump_revision();
+        }
+    }
+
+    /// Attenuation of a single light at `(x, y)`, swept only if it is not cached
+    /// for the current revision. Unchecked like
+    /// [`calculate_flat`](Sweeping::calculate_flat).
+    pub fn get(&mut self, x: usize, y: usize, intensity: f32) -> &[f32] {
+        self.clock += 1;
+        let key = (x, y, intensity.to_bits(), self.revision);
+        if self.entries.contains_key(&key) {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+            // Reuse the evicted grid's buffer
+            let mut grid = match self.evict_for_insert() {
+                Some(grid) => grid,
+                None => vec![0.0; self.width * self.height],
+            };
+            self.workspace.calculate_into(&self.decay, self.width, self.height, &[(x, y, intensity)], &mut grid);
+            self.entries.insert(key, Entry { grid, last_used: 0 });
+        }
+        let entry = self.entries.get_mut(&key).expect("entry was just inserted");
+        entry.last_used = self.clock;
+        &entry.grid
+    }
+
+    /// Drop every cached grid.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn grid_bytes(&self) -> usize {
+        self.width * self.height * std::mem::size_of::<f32>()
+    }
+
+    /// Grids that fit the budget, at least one
+    fn capacity(&self) -> usize {
+        (self.budget_bytes / self.grid_bytes().max(1)).max(1)
+    }
+
+    /// Evict the least recently used grid if the cache is full
+    fn evict_for_insert(&mut self) -> Option<Vec<f32>> {
+        if self.entries.len() < self.capacity() {
+            return None;
+        }
+        // The budget keeps the cache small, so a scan beats an ordered list
+        let (&key, _) = self.entries.iter().min_by_key(|(_, entry)| entry.last_used)?;
+        self.stats.evictions += 1;
+        self.entries.remove(&key).map(|entry| entry.grid)
+    }
+
+    /// Grids of older revisions can never be hit again, so drop them at once
+    fn bump_revision(&mut self) {
+        self.revision += 1;
+        self.entries.clear();
+    }
+}
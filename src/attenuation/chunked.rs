@@ -0,0 +1,270 @@
+//! Chunk-based world lighting with halo exchange.
+//!
+//! The world is split into square chunks that each own their decay and light.
+//! A chunk is lit by sweeping it with a halo ring copied from its neighbours'
+//! current light, which acts as fixed boundary values. Whenever light along a
+//! chunk's edge changes, its neighbours are queued and swept again, until no
+//! edge changes any more. Because every chunk sweep starts from the chunk's
+//! current light, the repeated exchange behaves like extra sweep rounds: the
+//! result matches [`Sweeping::calculate_converged`] on the full grid rather than
+//! a single [`Sweeping::calculate_many`] pass, which can be slightly darker
+//! where light turns many corners.
+//!
+//! Relighting only touches the chunks that an edited cell or an added, moved or
+//! removed light can reach above [`Sweeping::epsilon`]: those are reset and
+//! recomputed, everything else keeps its light. Every chunk sweep is seeded with
+//! the chunk's current light, so light only ever grows during a relight and the
+//! exchange is guaranteed to settle.
+
+use std::collections::VecDeque;
+
+use super::model::{DecayModel, Linear};
+use super::region::Region;
+use super::sweeping::{Boundary, Kernel, Sweeping};
+
+/// Work done by a [`ChunkedLighting::relight`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RelightStats {
+    /// Chunks that were reset because a change could reach them
+    pub chunks_reset: usize,
+    /// Chunk sweeps run, counting repeat sweeps during halo exchange
+    pub chunk_sweeps: usize,
+}
+
+/// A chunked world with per-chunk decay and light.
+#[derive(Debug, Clone)]
+pub struct ChunkedLighting<M: DecayModel = Linear> {
+    sweeping: Sweeping<M>,
+    chunk_size: usize,
+    chunks_x: usize,
+    chunks_y: usize,
+    decay: Vec<Vec<f32>>,
+    light: Vec<Vec<f32>>,
+    lights: Vec<(usize, usize, f32)>,
+    lit: Vec<(usize, usize, f32)>,
+    /// `(x, y, peak)` changes since the last relight
+    pending: Vec<(usize, usize, f32)>,
+}
+
+impl<M: DecayModel> ChunkedLighting<M> {
+    /// Create a `chunks_x x chunks_y` world of `chunk_size`-cell chunks filled with `decay`.
+    ///
+    /// Chunks are always swept with [`Boundary::Clamp`]; the world edge is dark.
+    /// Panics if `chunk_size` is smaller than the kernel's reach (1 cell, or 2
+    /// for [`Kernel::Sixteen`]).
+    pub fn new(sweeping: Sweeping<M>, chunk_size: usize, chunks_x: usize, chunks_y: usize, decay: f32) -> Self {
+        assert!(chunk_size > 0, "chunk size must be at least 1");
+        let cells = chunk_size * chunk_size;
+        let world = ChunkedLighting {
+            sweeping: Sweeping { boundary: Boundary::Clamp, ..sweeping },
+            chunk_size,
+            chunks_x,
+            chunks_y,
+            decay: vec![vec![decay; cells]; chunks_x * chunks_y],
+            light: vec![vec![0.0; cells]; chunks_x * chunks_y],
+            lights: Vec::new(),
+            lit: Vec::new(),
+            pending: Vec::new(),
+        };
+        assert!(
+            chunk_size >= world.halo(),
+            "chunk size {} is smaller than the kernel's reach of {}",
+            chunk_size,
+            world.halo()
+        );
+        world
+    }
+
+    /// Split a flat row-major decay grid into chunks. `width` and `height` must
+    /// be multiples of `chunk_size`, which is checked like in [`new`](Self::new).
+    pub fn from_flat(sweeping: Sweeping<M>, decay_flat: &[f32], width: usize, height: usize, chunk_size: usize) -> Self {
+        assert!(chunk_size > 0, "chunk size must be at least 1");
+        assert!(
+            width.is_multiple_of(chunk_size) && height.is_multiple_of(chunk_size),
+            "world size {}x{} is not a multiple of chunk size {}",
+            width,
+            height,
+            chunk_size
+        );
+        let mut world = Self::new(sweeping, chunk_size, width / chunk_size, height / chunk_size, 0.0);
+        for (i, &d) in decay_flat.iter().enumerate() {
+            let (c, l) = world.locate(i % width, i / width);
+            world.decay[c][l] = d;
+        }
+        world
+    }
+
+    /// World width in cells.
+    pub fn width(&self) -> usize {
+        self.chunks_x * self.chunk_size
+    }
+
+    /// World height in cells.
+    pub fn height(&self) -> usize {
+        self.chunks_y * self.chunk_size
+    }
+
+    /// Number of chunks along x and y.
+    pub fn chunks(&self) -> (usize, usize) {
+        (self.chunks_x, self.chunks_y)
+    }
+
+    /// Light of chunk `(cx, cy)`, row-major within the chunk.
+    pub fn chunk_light(&self, cx: usize, cy: usize) -> &[f32] {
+        &self.light[cy * self.chunks_x + cx]
+    }
+
+    /// Light at world cell `(x, y)`.
+    pub fn light_at(&self, x: usize, y: usize) -> f32 {
+        let (c, l) = self.locate(x, y);
+        self.light[c][l]
+    }
+
+    /// Light of the whole world as one flat row-major grid.
+    pub fn to_flat(&self) -> Vec<f32> {
+        let width = self.width();
+        (0..width * self.height()).map(|i| self.light_at(i % width, i / width)).collect()
+    }
+
+    /// Set the decay of world cell `(x, y)`. Takes effect on the next [`relight`](Self::relight).
+    pub fn set_decay(&mut self, x: usize, y: usize, decay: f32) {
+        let (c, l) = self.locate(x, y);
+        // Light leaving the neighbourhood of the cell is what can change
+        let around = Region::around(x, y, self.halo(), self.width(), self.height());
+        let mut peak = 0.0f32;
+        for ny in around.y0..around.y1 {
+            for nx in around.x0..around.x1 {
+                peak = peak.max(self.light_at(nx, ny));
+            }
+        }
+        self.decay[c][l] = decay;
+        self.pending.push((x, y, peak));
+    }
+
+    /// Replace the world's `(x, y, intensity)` lights. Takes effect on the next
+    /// [`relight`](Self::relight); only chunks near lights that changed are recomputed.
+    pub fn set_lights(&mut self, lights: &[(usize, usize, f32)]) {
+        for light in self.lit.iter().filter(|l| !lights.contains(l)) {
+            self.pending.push(*light);
+        }
+        for light in lights.iter().filter(|l| !self.lit.contains(l)) {
+            self.pending.push(*light);
+        }
+        self.lights = lights.to_vec();
+    }
+
+    /// Bring the light up to date with all decay and light changes since the last call.
+    pub fn relight(&mut self) -> RelightStats {
+        let mut stats = RelightStats::default();
+        if self.pending.is_empty() {
+            return stats;
+        }
+
+        let min_decay = self.decay.iter().flatten().copied().fold(f32::INFINITY, f32::min);
+        let (width, height) = (self.width(), self.height());
+        let mut queued = vec![false; self.decay.len()];
+        let mut queue = VecDeque::new();
+
+        for &(x, y, peak) in &self.pending {
+            let Some(steps) = self.sweeping.model.reach(peak, min_decay, self.sweeping.epsilon) else {
+                continue;
+            };
+            let reach = Region::around(x, y, steps.saturating_add(self.halo()), width, height);
+            for cy in reach.y0 / self.chunk_size..reach.y1.div_ceil(self.chunk_size) {
+                for cx in reach.x0 / self.chunk_size..reach.x1.div_ceil(self.chunk_size) {
+                    let c = cy * self.chunks_x + cx;
+                    if !queued[c] {
+                        queued[c] = true;
+                        queue.push_back(c);
+                        self.light[c].fill(0.0);
+                        stats.chunks_reset += 1;
+                    }
+                }
+            }
+        }
+        self.pending.clear();
+        self.lit = self.lights.clone();
+
+        while let Some(c) = queue.pop_front() {
+            queued[c] = false;
+            stats.chunk_sweeps += 1;
+            if self.sweep_chunk(c) {
+                let (cx, cy) = (c % self.chunks_x, c / self.chunks_x);
+                for ny in cy.saturating_sub(1)..(cy + 2).min(self.chunks_y) {
+                    for nx in cx.saturating_sub(1)..(cx + 2).min(self.chunks_x) {
+                        let n = ny * self.chunks_x + nx;
+                        if !queued[n] {
+                            queued[n] = true;
+                            queue.push_back(n);
+                        }
+                    }
+                }
+            }
+        }
+
+        stats
+    }
+
+    /// Width of the halo a chunk needs from its neighbours (the kernel's reach)
+    fn halo(&self) -> usize {
+        if self.sweeping.kernel == Kernel::Sixteen { 2 } else { 1 }
+    }
+
+    /// Chunk index and index within the chunk of world cell `(x, y)`
+    fn locate(&self, x: usize, y: usize) -> (usize, usize) {
+        let cs = self.chunk_size;
+        ((y / cs) * self.chunks_x + x / cs, (y % cs) * cs + x % cs)
+    }
+
+    /// Sweep chunk `c` with its halo as boundary values. Returns whether light
+    /// within halo distance of the chunk edge grew by more than `epsilon`.
+    fn sweep_chunk(&mut self, c: usize) -> bool {
+        let (cs, halo) = (self.chunk_size, self.halo());
+        let pw = cs + 2 * halo;
+        // World position of the padded grid's top-left cell
+        let ox = (c % self.chunks_x * cs) as isize - halo as isize;
+        let oy = (c / self.chunks_x * cs) as isize - halo as isize;
+        let (width, height) = (self.width() as isize, self.height() as isize);
+
+        let mut decay = Vec::with_capacity(pw * pw);
+        let mut seed = Vec::with_capacity(pw * pw);
+        for py in 0..pw as isize {
+            for px in 0..pw as isize {
+                let (wx, wy) = (ox + px, oy + py);
+                if wx < 0 || wy < 0 || wx >= width || wy >= height {
+                    decay.push(1.0);
+                    seed.push(0.0);
+                } else {
+                    let (n, l) = self.locate(wx as usize, wy as usize);
+                    decay.push(self.decay[n][l]);
+                    seed.push(self.light[n][l]);
+                }
+            }
+        }
+        let interior = Region { x0: halo, y0: halo, x1: halo + cs, y1: halo + cs };
+        for &(x, y, intensity) in &self.lights {
+            let (px, py) = ((x as isize - ox) as usize, (y as isize - oy) as usize);
+            if interior.contains(px, py) {
+                let i = py * pw + px;
+                seed[i] = seed[i].max(intensity);
+            }
+        }
+
+        let swept = self.sweeping.sweep_seeded(&decay, pw, pw, interior, seed);
+
+        let mut edge_changed = false;
+        let light = &mut self.light[c];
+        for y in 0..cs {
+            for x in 0..cs {
+                let new = swept[(y + halo) * pw + x + halo];
+                let old = &mut light[y * cs + x];
+                let on_edge = x < halo || y < halo || x >= cs - halo || y >= cs - halo;
+                if on_edge && new > *old + self.sweeping.epsilon {
+                    edge_changed = true;
+                }
+                *old = new;
+            }
+        }
+        edge_changed
+    }
+}
@@ -0,0 +1,58 @@
+//! Self-lit cells driven by a per-cell emission grid.
+//!
+//! Every cell with a positive emission is seeded as a source of that intensity,
+//! so lava or glowing crystals light their surroundings in the same sweep as the
+//! placed lights. Light leaving an emissive cell, or passing through it, is still
+//! charged that cell's decay like any other.
+
+use super::model::DecayModel;
+use super::sweeping::Sweeping;
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Calculate attenuation with emissive cells plus `(x, y, intensity)` lights.
+    ///
+    /// `emission` has one intensity per cell (row-major, same size as
+    /// `decay_flat`); zero means the cell does not emit. The result is the same
+    /// as [`calculate_many`](Self::calculate_many) with every emissive cell added
+    /// as a light.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    pub fn calculate_emissive(
+        &self,
+        decay_flat: &[f32],
+        emission: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+    ) -> Vec<f32> {
+        let mut sources = emissive_cells(emission, width, |&e| e > 0.0);
+        sources.extend_from_slice(lights);
+        self.calculate_many(decay_flat, width, height, &sources)
+    }
+
+    /// Per-channel variant of [`calculate_emissive`](Self::calculate_emissive),
+    /// with `[r, g, b]` emission and decay per cell (see
+    /// [`calculate_rgb`](Self::calculate_rgb)).
+    pub fn calculate_rgb_emissive(
+        &self,
+        decay_rgb: &[[f32; 3]],
+        emission_rgb: &[[f32; 3]],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, [f32; 3])],
+    ) -> Vec<[f32; 3]> {
+        let mut sources = emissive_cells(emission_rgb, width, |e| e.iter().any(|&c| c > 0.0));
+        sources.extend_from_slice(lights);
+        self.calculate_rgb_many(decay_rgb, width, height, &sources)
+    }
+}
+
+/// `(x, y, value)` for every emissive cell of a flat grid
+fn emissive_cells<E: Copy>(emission: &[E], width: usize, emits: impl Fn(&E) -> bool) -> Vec<(usize, usize, E)> {
+    emission
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| emits(e))
+        .map(|(i, &e)| (i % width, i / width, e))
+        .collect()
+}
@@ -0,0 +1,70 @@
+//! Input validation for the checked attenuation entry points.
+
+use std::fmt;
+
+/// Errors reported by the checked (`try_*`) attenuation functions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LightError {
+    /// The grid has zero width or zero height.
+    EmptyGrid { width: usize, height: usize },
+    /// `width * height` overflows `usize`.
+    GridTooLarge { width: usize, height: usize },
+    /// `decay_flat.len()` does not equal `width * height`.
+    SizeMismatch { expected: usize, actual: usize },
+    /// The light source lies outside the grid.
+    SourceOutOfBounds { x: usize, y: usize, width: usize, height: usize },
+    /// A decay value is NaN, infinite or negative.
+    InvalidDecay { index: usize, value: f32 },
+}
+
+impl fmt::Display for LightError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            LightError::EmptyGrid { width, height } => {
+                write!(f, "grid must not be empty (got {}x{})", width, height)
+            }
+            LightError::GridTooLarge { width, height } => {
+                write!(f, "grid of {}x{} cells is too large", width, height)
+            }
+            LightError::SizeMismatch { expected, actual } => {
+                write!(f, "decay grid has {} cells, expected {}", actual, expected)
+            }
+            LightError::SourceOutOfBounds { x, y, width, height } => {
+                write!(f, "light source ({}, {}) is outside the {}x{} grid", x, y, width, height)
+            }
+            LightError::InvalidDecay { index, value } => {
+                write!(f, "decay at index {} must be finite and non-negative (got {})", index, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LightError {}
+
+/// Check that a flat decay grid is non-empty, correctly sized and contains
+/// only finite, non-negative values.
+pub fn validate_decay(decay_flat: &[f32], width: usize, height: usize) -> Result<(), LightError> {
+    if width == 0 || height == 0 {
+        return Err(LightError::EmptyGrid { width, height });
+    }
+    let expected = width.checked_mul(height).ok_or(LightError::GridTooLarge { width, height })?;
+    if decay_flat.len() != expected {
+        return Err(LightError::SizeMismatch { expected, actual: decay_flat.len() });
+    }
+    if let Some((index, &value)) = decay_flat
+        .iter()
+        .enumerate()
+        .find(|&(_, &d)| !d.is_finite() || d < 0.0)
+    {
+        return Err(LightError::InvalidDecay { index, value });
+    }
+    Ok(())
+}
+
+/// Check that a light source lies inside a `width x height` grid.
+pub fn validate_source(x: usize, y: usize, width: usize, height: usize) -> Result<(), LightError> {
+    if x >= width || y >= height {
+        return Err(LightError::SourceOutOfBounds { x, y, width, height });
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! Incremental recompute after local edits to the decay grid.
+//!
+//! Changing the decay of a cell only alters light that *leaves* that cell, and
+//! no path through it can carry more than the cell's previous attenuation. So
+//! with `t_max` the best per-step transmittance in the grid, a cell at Chebyshev
+//! distance `d` from an edit can change by at most `att[c] * t_max^(d - 1)`.
+//! Everything beyond the distance where that bound drops under
+//! [`Sweeping::epsilon`] keeps its previous value.
+//!
+//! The affected cells are reset and swept again together with a margin around
+//! them. The cells bordering the swept area only have their final values, while
+//! a full sweep sees them part-way through its schedule, so light from there can
+//! come out too bright. The margin is wide enough for that light to fade below
+//! `epsilon` before it reaches an affected cell, and only the affected cells take
+//! the new values, so repeated edits do not drift away from a full recompute.
+
+use super::model::DecayModel;
+use super::region::Region;
+use super::sweeping::{Boundary, Kernel, Sweeping, join_passes, merge_max, run_forward_sweeps, run_reverse_sweeps};
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Update an attenuation grid in place after some decay cells changed.
+    ///
+    /// * `decay_flat` - the decay grid *after* the edit
+    /// * `lights` - the `(x, y, intensity)` sources `previous` was computed for
+    ///   (a single light is `&[(x, y, intensity)]`)
+    /// * `previous` - attenuation before the edit; overwritten with the new result
+    /// * `changes` - `(index, old_decay)` for every edited cell
+    ///
+    /// Returns the region whose values were updated (empty if nothing changed);
+    /// a margin around it is swept as well. Cells match a from-scratch
+    /// [`calculate_many`](Self::calculate_many) to within [`epsilon`](Self::epsilon),
+    /// also after many edits applied one after another to the same grid.
+    ///
+    /// With a wrapping [`boundary`](Self::boundary) the whole grid is recomputed.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    pub fn update_flat(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+        previous: &mut [f32],
+        changes: &[(usize, f32)],
+    ) -> Region {
+        if changes.is_empty() {
+            return Region::empty();
+        }
+        if self.boundary != Boundary::Clamp {
+            // Light can wrap back into the region across a seam; recompute everything
+            self.calculate_many_into(decay_flat, width, height, lights, previous);
+            return Region::full(width, height);
+        }
+
+        // Best transmittance any step can have, before or after the edit
+        let min_decay_now = decay_flat.iter().copied().fold(f32::INFINITY, f32::min);
+        let min_decay = changes.iter().map(|&(_, old)| old).fold(min_decay_now, f32::min);
+        let ring = if self.kernel == Kernel::Sixteen { 2 } else { 1 };
+
+        let mut affected = Region::empty();
+        for &(idx, old) in changes {
+            let (cx, cy) = (idx % width, idx / width);
+            let (peak, extra) = if self.kernel == Kernel::Sixteen {
+                // Knight moves from the 8 neighbours straddle this cell too
+                let around = Region::around(cx, cy, 1, width, height);
+                (region_peak(previous, width, around), 2)
+            } else {
+                (self.model.transmit(previous[idx], old.min(decay_flat[idx]), 1.0), 1)
+            };
+            let radius = match self.model.reach(peak, min_decay, self.epsilon) {
+                Some(steps) => steps.saturating_add(extra),
+                None => 0,
+            };
+            affected = affected.union(&Region::around(cx, cy, radius, width, height));
+        }
+
+        // A full sweep leaves cells outside the lights' window at their seeds, so
+        // there they are boundary values the sweep schedule agrees on
+        let window = self.window_for(min_decay_now, width, height, lights);
+
+        // Inside the window, cells just outside the swept area only have their
+        // final values, which can be brighter than what the full sweep's schedule
+        // sees at that point. Sweep a margin around the affected cells wide enough
+        // that light entering from there fades below epsilon before reaching them.
+        let mut margin = 0;
+        let swept = loop {
+            let swept = affected.expand(margin, width, height).intersect(&window);
+            if swept == window || swept.is_empty() {
+                break swept;
+            }
+            let outer = swept.expand(ring, width, height).intersect(&window);
+            let needed = match self.model.reach(ring_peak(previous, width, outer, swept), min_decay, self.epsilon) {
+                Some(steps) => steps.saturating_add(ring),
+                None => 0,
+            };
+            if needed <= margin {
+                break swept;
+            }
+            margin = needed;
+        };
+
+        let mut forward = previous.to_vec();
+        if !swept.is_empty() {
+            reset_to_seeds(&mut forward, width, swept, lights);
+            let mut reverse = forward.clone();
+            join_passes(
+                &mut Default::default(),
+                |front| run_forward_sweeps(self, decay_flat, &mut forward, width, height, swept, front),
+                |front| run_reverse_sweeps(self, decay_flat, &mut reverse, width, height, swept, front),
+            );
+            merge_max(&mut forward, &reverse, width, swept);
+        }
+
+        // Only the affected cells take new values; the margin keeps its old ones
+        reset_to_seeds(previous, width, affected, lights);
+        let inside = affected.intersect(&window);
+        for y in inside.y0..inside.y1 {
+            let cells = y * width + inside.x0..y * width + inside.x1;
+            previous[cells.clone()].copy_from_slice(&forward[cells]);
+        }
+
+        affected
+    }
+}
+
+/// Brightest cell of `region`
+fn region_peak(att: &[f32], width: usize, region: Region) -> f32 {
+    (region.y0..region.y1)
+        .flat_map(|y| att[y * width + region.x0..y * width + region.x1].iter())
+        .copied()
+        .fold(0.0f32, f32::max)
+}
+
+/// Brightest cell of `outer` that is not in `inner`
+fn ring_peak(att: &[f32], width: usize, outer: Region, inner: Region) -> f32 {
+    let mut peak = 0.0f32;
+    for y in outer.y0..outer.y1 {
+        let row = &att[y * width..(y + 1) * width];
+        if y < inner.y0 || y >= inner.y1 {
+            peak = row[outer.x0..outer.x1].iter().copied().fold(peak, f32::max);
+        } else {
+            peak = row[outer.x0..inner.x0].iter().chain(&row[inner.x1..outer.x1]).copied().fold(peak, f32::max);
+        }
+    }
+    peak
+}
+
+/// Reset `region` to the light seeds, as before a sweep
+fn reset_to_seeds(att: &mut [f32], width: usize, region: Region, lights: &[(usize, usize, f32)]) {
+    for y in region.y0..region.y1 {
+        att[y * width + region.x0..y * width + region.x1].fill(0.0);
+    }
+    for &(x, y, intensity) in lights {
+        if region.contains(x, y) {
+            let idx = y * width + x;
+            att[idx] = att[idx].max(intensity);
+        }
+    }
+}
@@ -3,20 +3,52 @@
 //! This module provides the sweeping algorithm for calculating light attenuation
 //! through a decay grid using a flat memory layout for optimal performance.
 
+pub mod algorithm;
+pub mod baked;
+pub mod cache;
+pub mod chunked;
+mod emission;
+pub mod error;
+mod incremental;
+pub mod model;
+pub mod reference;
+pub mod region;
+#[cfg(test)]
+pub(crate) mod scalar;
+pub mod shape;
+pub mod sky;
+pub mod spot;
 pub mod sweeping;
+pub mod transmittance;
+#[cfg(feature = "parallel")]
+mod wavefront;
+pub mod workspace;
 
-pub use sweeping::{Sweeping, flatten_grid};
+pub use algorithm::{AttenuationAlgorithm, FloodFill, Raycast};
+pub use baked::{BakedLighting, Combine};
+pub use cache::{AttenuationCache, CacheStats};
+pub use chunked::{ChunkedLighting, RelightStats};
+pub use error::{LightError, validate_decay, validate_source};
+pub use reference::{BestPath, ErrorStats, compare_grids, sweep_error};
+pub use model::{DecayModel, Exponential, Linear, Subtractive};
+pub use region::Region;
+pub use shape::Shape;
+pub use sky::SkyLight;
+pub use spot::Cone;
+pub use sweeping::{Boundary, Convergence, Kernel, Sweeping, flatten_grid};
+pub use transmittance::TransmittanceGrid;
+pub use workspace::SweepWorkspace;
 
 /// Converts an attenuation grid to a formatted string for debugging
-pub fn attenuation_to_string(attenuation: &Vec<Vec<f32>>) -> String {
+pub fn attenuation_to_string(attenuation: &[Vec<f32>]) -> String {
     let width = attenuation.len();
     let height = if width > 0 { attenuation[0].len() } else { 0 };
     let mut result = String::new();
 
     // Print y going down (top to bottom)
     for y in 0..height {
-        for x in 0..width {
-            result.push_str(&format!("{:5.2} ", attenuation[x][y]));
+        for column in attenuation {
+            result.push_str(&format!("{:5.2} ", column[y]));
         }
         result.push('\n');
     }
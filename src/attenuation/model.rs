@@ -0,0 +1,116 @@
+//! Transfer functions for light moving from one cell to the next.
+//!
+//! A [`DecayModel`] decides how much of a cell's light reaches a neighbour given
+//! the cell's decay and the step's distance multiplier (1 for orthogonal steps,
+//! [`Sweeping::diagonal_decay_mult`](super::Sweeping::diagonal_decay_mult) for
+//! diagonals, √5 for knight moves). [`Sweeping`](super::Sweeping) is generic over
+//! the model, so each one gets its own monomorphised, inlined sweep loops.
+
+use std::fmt::Debug;
+
+use super::sweeping::reach;
+
+/// Per-step light transfer function.
+///
+/// Implementations must be monotone: more light in, or less decay, never gives
+/// less light out, and the result never exceeds `light`. The sweep and the
+/// [`BestPath`](super::BestPath) reference solver both rely on this.
+///
+/// A step is split into a per-cell [`factor`](Self::factor), which only depends
+/// on the decay and can be precomputed (see
+/// [`TransmittanceGrid`](super::TransmittanceGrid)), and [`apply`](Self::apply),
+/// which combines it with the light.
+pub trait DecayModel: Clone + Debug + Send + Sync {
+    /// Transmittance factor of a step of distance multiplier `mult` out of a
+    /// cell with `decay`.
+    fn factor(&self, decay: f32, mult: f32) -> f32;
+
+    /// Light left after `light` crosses a step with transmittance `factor`.
+    /// Never negative.
+    fn apply(&self, light: f32, factor: f32) -> f32;
+
+    /// Light arriving at a neighbour when `light` leaves a cell with `decay`
+    /// over a step of distance multiplier `mult`.
+    #[inline(always)]
+    fn transmit(&self, light: f32, decay: f32, mult: f32) -> f32 {
+        self.apply(light, self.factor(decay, mult))
+    }
+
+    /// Number of orthogonal steps light starting at `peak` can travel through
+    /// cells of decay at least `min_decay` while staying at or above `epsilon`.
+    /// `None` if `peak` is already below `epsilon`; `usize::MAX` if unbounded.
+    fn reach(&self, peak: f32, min_decay: f32, epsilon: f32) -> Option<usize>;
+}
+
+/// Linear transmittance `light * (1 - decay * mult)`, clamped at zero (the default).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Linear;
+
+impl DecayModel for Linear {
+    #[inline(always)]
+    fn factor(&self, decay: f32, mult: f32) -> f32 {
+        1.0 - decay * mult
+    }
+
+    #[inline(always)]
+    fn apply(&self, light: f32, factor: f32) -> f32 {
+        (light * factor).max(0.0)
+    }
+
+    fn reach(&self, peak: f32, min_decay: f32, epsilon: f32) -> Option<usize> {
+        reach(peak, (1.0 - min_decay).clamp(0.0, 1.0), epsilon)
+    }
+}
+
+/// Beer–Lambert transmittance `light * exp(-decay * mult)`.
+///
+/// Never reaches zero, so even thick walls let a trace of light through.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Exponential;
+
+impl DecayModel for Exponential {
+    #[inline(always)]
+    fn factor(&self, decay: f32, mult: f32) -> f32 {
+        (-decay * mult).exp()
+    }
+
+    #[inline(always)]
+    fn apply(&self, light: f32, factor: f32) -> f32 {
+        light * factor
+    }
+
+    fn reach(&self, peak: f32, min_decay: f32, epsilon: f32) -> Option<usize> {
+        reach(peak, (-min_decay.max(0.0)).exp(), epsilon)
+    }
+}
+
+/// Subtractive light levels `light - decay * mult`, clamped at zero.
+///
+/// Like block-game light levels: decay is a fixed cost per step rather than a
+/// fraction, so light has a hard maximum range.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Subtractive;
+
+impl DecayModel for Subtractive {
+    /// The factor is the light lost per step rather than a fraction kept
+    #[inline(always)]
+    fn factor(&self, decay: f32, mult: f32) -> f32 {
+        decay * mult
+    }
+
+    #[inline(always)]
+    fn apply(&self, light: f32, factor: f32) -> f32 {
+        (light - factor).max(0.0)
+    }
+
+    fn reach(&self, peak: f32, min_decay: f32, epsilon: f32) -> Option<usize> {
+        if peak < epsilon {
+            return None;
+        }
+        if min_decay <= 0.0 {
+            return Some(usize::MAX);
+        }
+        let steps = ((peak - epsilon) / min_decay).floor();
+        Some(if steps.is_finite() { steps as usize } else { usize::MAX })
+    }
+}
@@ -0,0 +1,213 @@
+//! Exact best-path reference solver.
+//!
+//! Light reaching a cell is the maximum, over all paths from the source, of the
+//! light left after applying the decay model at every step (the same rule as
+//! [`Sweeping`]). Since no step can add light, that maximum can be found exactly
+//! with Dijkstra's algorithm on a max-heap. This is far slower than the
+//! sweep, but gives a ground truth to measure the sweep's schedule against.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use super::model::{DecayModel, Linear};
+use super::sweeping::{Boundary, Kernel, Sweeping};
+
+/// Exact maximum-transmission solver over the same neighbourhood graph as [`Sweeping`].
+#[derive(Debug, Clone)]
+pub struct BestPath<M: DecayModel = Linear> {
+    /// Multiplier for diagonal distance (default: √2 ≈ 1.414)
+    pub diagonal_decay_mult: f32,
+    /// Neighbourhood used for propagation (default: [`Kernel::Eight`])
+    pub kernel: Kernel,
+    /// Which grid edges are joined (default: [`Boundary::Clamp`]). Unlike the
+    /// sweep, paths may cross a seam any number of times.
+    pub boundary: Boundary,
+    /// Per-step transfer function (see [`DecayModel`])
+    pub model: M,
+}
+
+impl<M: DecayModel + Default> Default for BestPath<M> {
+    fn default() -> Self {
+        BestPath::matching(&Sweeping::default())
+    }
+}
+
+/// Per-cell error of an approximate attenuation grid against an exact one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorStats {
+    /// Largest absolute difference
+    pub max_error: f32,
+    /// Mean absolute difference over all cells
+    pub mean_error: f32,
+    /// Cell `(x, y)` where `max_error` occurs
+    pub max_error_at: (usize, usize),
+}
+
+impl BestPath {
+    /// Create a reference solver with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<M: DecayModel> BestPath<M> {
+    /// Create a reference solver using the same propagation rule and boundary as `sweeping`.
+    pub fn matching(sweeping: &Sweeping<M>) -> Self {
+        BestPath {
+            diagonal_decay_mult: sweeping.diagonal_decay_mult,
+            kernel: sweeping.kernel,
+            boundary: sweeping.boundary,
+            model: sweeping.model.clone(),
+        }
+    }
+
+    /// Exact attenuation from a single light (row-major: index = y * width + x).
+    pub fn calculate_flat(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        light_x: usize,
+        light_y: usize,
+        source_intensity: f32,
+    ) -> Vec<f32> {
+        self.calculate_many(decay_flat, width, height, &[(light_x, light_y, source_intensity)])
+    }
+
+    /// Exact combined attenuation from several `(x, y, intensity)` lights.
+    pub fn calculate_many(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+    ) -> Vec<f32> {
+        let mut att = vec![0.0f32; width * height];
+        self.calculate_many_into(decay_flat, width, height, lights, &mut att);
+        att
+    }
+
+    /// [`calculate_many`](Self::calculate_many) into `att` (`att.len() == width * height`).
+    pub(super) fn calculate_many_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+        att: &mut [f32],
+    ) {
+        att.fill(0.0);
+        let mut done = vec![false; width * height];
+        let mut heap = BinaryHeap::new();
+
+        for &(x, y, intensity) in lights {
+            let idx = y * width + x;
+            if intensity > att[idx] {
+                att[idx] = intensity;
+                heap.push(Entry { value: intensity, idx });
+            }
+        }
+
+        let steps = self.kernel.steps(self.diagonal_decay_mult);
+        // Index of cell (cx, cy), wrapped across joined edges; `None` off the grid
+        let (wrap_x, wrap_y) = (self.boundary.wraps_x(), self.boundary.wraps_y());
+        let cell = |cx: isize, cy: isize| {
+            let cx = if wrap_x { cx.rem_euclid(width as isize) } else { cx };
+            let cy = if wrap_y { cy.rem_euclid(height as isize) } else { cy };
+            let inside = cx >= 0 && cy >= 0 && cx < width as isize && cy < height as isize;
+            inside.then(|| cy as usize * width + cx as usize)
+        };
+        while let Some(Entry { value, idx }) = heap.pop() {
+            if done[idx] || value < att[idx] {
+                continue;
+            }
+            done[idx] = true;
+
+            let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+            for &(dx, dy, mult) in &steps {
+                let Some(ni) = cell(x + dx, y + dy) else { continue };
+                if done[ni] {
+                    continue;
+                }
+
+                let decay = if dx.abs() + dy.abs() == 3 {
+                    // Knight move: also charged for the two cells it straddles
+                    let (m1, m2) = if dx.abs() == 2 {
+                        ((x + dx / 2, y), (x + dx / 2, y + dy))
+                    } else {
+                        ((x, y + dy / 2), (x + dx, y + dy / 2))
+                    };
+                    let at = |(cx, cy): (isize, isize)| cell(cx, cy).map_or(f32::INFINITY, |i| decay_flat[i]);
+                    decay_flat[idx].max(0.5 * (at(m1) + at(m2)))
+                } else {
+                    decay_flat[idx]
+                };
+
+                let candidate = self.model.transmit(value, decay, mult);
+                if candidate > att[ni] {
+                    att[ni] = candidate;
+                    heap.push(Entry { value: candidate, idx: ni });
+                }
+            }
+        }
+    }
+}
+
+/// Compare an approximate attenuation grid against an exact one, cell by cell.
+pub fn compare_grids(approx: &[f32], exact: &[f32], width: usize) -> ErrorStats {
+    let mut stats = ErrorStats { max_error: 0.0, mean_error: 0.0, max_error_at: (0, 0) };
+    let mut total = 0.0f64;
+
+    for (i, (a, e)) in approx.iter().zip(exact).enumerate() {
+        let error = (a - e).abs();
+        total += error as f64;
+        if error > stats.max_error {
+            stats.max_error = error;
+            stats.max_error_at = (i % width, i / width);
+        }
+    }
+
+    if !exact.is_empty() {
+        stats.mean_error = (total / exact.len() as f64) as f32;
+    }
+    stats
+}
+
+/// Measure how far `sweeping` is from the exact best-path result for `lights`.
+pub fn sweep_error<M: DecayModel>(
+    sweeping: &Sweeping<M>,
+    decay_flat: &[f32],
+    width: usize,
+    height: usize,
+    lights: &[(usize, usize, f32)],
+) -> ErrorStats {
+    let approx = sweeping.calculate_many(decay_flat, width, height, lights);
+    let exact = BestPath::matching(sweeping).calculate_many(decay_flat, width, height, lights);
+    compare_grids(&approx, &exact, width)
+}
+
+/// Max-heap entry ordered by light value
+struct Entry {
+    value: f32,
+    idx: usize,
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.total_cmp(&other.value).then_with(|| other.idx.cmp(&self.idx))
+    }
+}
@@ -0,0 +1,93 @@
+//! Rectangular cell regions used to restrict sweeps to part of a grid.
+
+/// A rectangle of grid cells, `x0..x1` by `y0..y1` (upper bounds exclusive).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Region {
+    /// The whole `width x height` grid.
+    pub fn full(width: usize, height: usize) -> Self {
+        Region { x0: 0, y0: 0, x1: width, y1: height }
+    }
+
+    /// Cells within Chebyshev distance `radius` of `(x, y)`, clipped to the grid.
+    pub fn around(x: usize, y: usize, radius: usize, width: usize, height: usize) -> Self {
+        Region {
+            x0: x.saturating_sub(radius),
+            y0: y.saturating_sub(radius),
+            x1: x.saturating_add(radius).saturating_add(1).min(width),
+            y1: y.saturating_add(radius).saturating_add(1).min(height),
+        }
+    }
+
+    /// Smallest region covering both `self` and `other`.
+    pub fn union(&self, other: &Region) -> Self {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        Region {
+            x0: self.x0.min(other.x0),
+            y0: self.y0.min(other.y0),
+            x1: self.x1.max(other.x1),
+            y1: self.y1.max(other.y1),
+        }
+    }
+
+    /// Cells covered by both `self` and `other`.
+    pub fn intersect(&self, other: &Region) -> Self {
+        let region = Region {
+            x0: self.x0.max(other.x0),
+            y0: self.y0.max(other.y0),
+            x1: self.x1.min(other.x1),
+            y1: self.y1.min(other.y1),
+        };
+        if region.is_empty() { Region::empty() } else { region }
+    }
+
+    /// `self` grown by `margin` cells on every side, clipped to the grid.
+    pub fn expand(&self, margin: usize, width: usize, height: usize) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        Region {
+            x0: self.x0.saturating_sub(margin),
+            y0: self.y0.saturating_sub(margin),
+            x1: self.x1.saturating_add(margin).min(width),
+            y1: self.y1.saturating_add(margin).min(height),
+        }
+    }
+
+    /// An empty region (covers no cells).
+    pub fn empty() -> Self {
+        Region { x0: 0, y0: 0, x1: 0, y1: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x0 >= self.x1 || self.y0 >= self.y1
+    }
+
+    pub fn contains(&self, x: usize, y: usize) -> bool {
+        x >= self.x0 && x < self.x1 && y >= self.y0 && y < self.y1
+    }
+
+    pub fn width(&self) -> usize {
+        self.x1.saturating_sub(self.x0)
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1.saturating_sub(self.y0)
+    }
+
+    /// Number of cells covered.
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
+    }
+}
@@ -0,0 +1,274 @@
+//! Cell-by-cell reference sweeps.
+//!
+//! These are the straightforward per-cell loops the row-wise sweeps in
+//! [`sweeping`](super::sweeping) replaced. They are only built for tests, which
+//! check that both produce bit-identical grids.
+
+use super::model::DecayModel;
+use super::region::Region;
+use super::sweeping::{KNIGHT_DECAY_MULT, Kernel, Sweeping};
+
+/// Calculate light propagation from neighbor to current cell
+#[inline(always)]
+fn propagate<M: DecayModel>(model: &M, att: &[f32], decay: &[f32], ni: usize, mult: f32) -> f32 {
+    model.transmit(att[ni], decay[ni], mult)
+}
+
+/// Calculate light propagation along a knight move from `ni`.
+///
+/// The move straddles the two cells `m1` and `m2` it passes between, so it is
+/// charged the larger of the source decay and their average decay; light cannot
+/// jump cleanly past a wall corner.
+#[inline(always)]
+fn propagate_knight<M: DecayModel>(model: &M, att: &[f32], decay: &[f32], ni: usize, m1: usize, m2: usize) -> f32 {
+    let d = decay[ni].max(0.5 * (decay[m1] + decay[m2]));
+    model.transmit(att[ni], d, KNIGHT_DECAY_MULT)
+}
+
+// ============================================================================
+// Four sweep patterns, one cell at a time
+// ============================================================================
+//
+// Each sweep visits only the cells of `r`, but reads neighbours anywhere in the
+// `w x h` grid, so values outside the region act as fixed boundary conditions.
+//
+// `DIAG` enables the diagonal neighbours and `KNIGHT` the knight moves; they are
+// const generics so every kernel gets its own branch-free inner loop.
+
+/// Sweep from top-left to bottom-right, checking left/up/up-left neighbors
+#[inline]
+fn sweep_tl_to_br<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    _h: usize,
+    r: Region,
+    diag: f32,
+) {
+    for y in r.y0..r.y1 {
+        for x in r.x0..r.x1 {
+            let idx = y * w + x;
+            let mut max_prop = att[idx];
+            if x > 0 {
+                max_prop = max_prop.max(propagate(model, att, decay, idx - 1, 1.0));
+            }
+            if y > 0 {
+                max_prop = max_prop.max(propagate(model, att, decay, idx - w, 1.0));
+            }
+            if DIAG && x > 0 && y > 0 {
+                max_prop = max_prop.max(propagate(model, att, decay, idx - w - 1, diag));
+            }
+            if KNIGHT {
+                if x > 1 && y > 0 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - w - 2, idx - w - 1, idx - 1));
+                }
+                if x > 0 && y > 1 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - 2 * w - 1, idx - w - 1, idx - w));
+                }
+            }
+            att[idx] = max_prop;
+        }
+    }
+}
+
+/// Sweep from bottom-right to top-left, checking right/down/down-right neighbors
+#[inline]
+fn sweep_br_to_tl<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+    diag: f32,
+) {
+    for y in (r.y0..r.y1).rev() {
+        for x in (r.x0..r.x1).rev() {
+            let idx = y * w + x;
+            let mut max_prop = att[idx];
+            if x + 1 < w {
+                max_prop = max_prop.max(propagate(model, att, decay, idx + 1, 1.0));
+            }
+            if y + 1 < h {
+                max_prop = max_prop.max(propagate(model, att, decay, idx + w, 1.0));
+            }
+            if DIAG && x + 1 < w && y + 1 < h {
+                max_prop = max_prop.max(propagate(model, att, decay, idx + w + 1, diag));
+            }
+            if KNIGHT {
+                if x + 2 < w && y + 1 < h {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + w + 2, idx + w + 1, idx + 1));
+                }
+                if x + 1 < w && y + 2 < h {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + 2 * w + 1, idx + w + 1, idx + w));
+                }
+            }
+            att[idx] = max_prop;
+        }
+    }
+}
+
+/// Sweep top-down, checking left/up and both upper diagonals
+#[inline]
+fn sweep_down<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    _h: usize,
+    r: Region,
+    diag: f32,
+) {
+    for y in r.y0..r.y1 {
+        for x in r.x0..r.x1 {
+            let idx = y * w + x;
+            let mut max_prop = att[idx];
+            if x > 0 {
+                max_prop = max_prop.max(propagate(model, att, decay, idx - 1, 1.0));
+            }
+            if y > 0 {
+                max_prop = max_prop.max(propagate(model, att, decay, idx - w, 1.0));
+                if DIAG && x > 0 {
+                    max_prop = max_prop.max(propagate(model, att, decay, idx - w - 1, diag));
+                }
+                if DIAG && x + 1 < w {
+                    max_prop = max_prop.max(propagate(model, att, decay, idx - w + 1, diag));
+                }
+            }
+            if KNIGHT {
+                if y > 0 && x > 1 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - w - 2, idx - w - 1, idx - 1));
+                }
+                if y > 0 && x + 2 < w {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - w + 2, idx - w + 1, idx + 1));
+                }
+                if y > 1 && x > 0 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - 2 * w - 1, idx - w - 1, idx - w));
+                }
+                if y > 1 && x + 1 < w {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx - 2 * w + 1, idx - w + 1, idx - w));
+                }
+            }
+            att[idx] = max_prop;
+        }
+    }
+}
+
+/// Sweep bottom-up, checking right/down and both lower diagonals
+#[inline]
+fn sweep_up<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+    diag: f32,
+) {
+    for y in (r.y0..r.y1).rev() {
+        for x in (r.x0..r.x1).rev() {
+            let idx = y * w + x;
+            let mut max_prop = att[idx];
+            if x + 1 < w {
+                max_prop = max_prop.max(propagate(model, att, decay, idx + 1, 1.0));
+            }
+            if y + 1 < h {
+                max_prop = max_prop.max(propagate(model, att, decay, idx + w, 1.0));
+                if DIAG && x > 0 {
+                    max_prop = max_prop.max(propagate(model, att, decay, idx + w - 1, diag));
+                }
+                if DIAG && x + 1 < w {
+                    max_prop = max_prop.max(propagate(model, att, decay, idx + w + 1, diag));
+                }
+            }
+            if KNIGHT {
+                if y + 1 < h && x + 2 < w {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + w + 2, idx + w + 1, idx + 1));
+                }
+                if y + 1 < h && x > 1 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + w - 2, idx + w - 1, idx - 1));
+                }
+                if y + 2 < h && x + 1 < w {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + 2 * w + 1, idx + w + 1, idx + w));
+                }
+                if y + 2 < h && x > 0 {
+                    max_prop = max_prop.max(propagate_knight(model, att, decay, idx + 2 * w - 1, idx + w - 1, idx + w));
+                }
+            }
+            att[idx] = max_prop;
+        }
+    }
+}
+
+// ============================================================================
+// Forward and reverse pass orchestration
+// ============================================================================
+
+/// Forward sweeps: TL→BR, BR→TL, Down, Up
+#[inline]
+fn forward_sweeps<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+    diag: f32,
+) {
+    sweep_tl_to_br::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_br_to_tl::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_down::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_up::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+}
+
+/// Reverse sweeps: Up, Down, BR→TL, TL→BR (opposite order)
+#[inline]
+fn reverse_sweeps<M: DecayModel, const DIAG: bool, const KNIGHT: bool>(
+    model: &M,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+    diag: f32,
+) {
+    sweep_up::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_down::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_br_to_tl::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+    sweep_tl_to_br::<M, DIAG, KNIGHT>(model, decay, att, w, h, r, diag);
+}
+
+/// Forward pass with the sweeps specialised for the kernel and decay model of `s`
+pub(crate) fn run_forward_sweeps<M: DecayModel>(
+    s: &Sweeping<M>,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+) {
+    let (model, diag) = (&s.model, s.diagonal_decay_mult);
+    match s.kernel {
+        Kernel::Four => forward_sweeps::<M, false, false>(model, decay, att, w, h, r, diag),
+        Kernel::Eight => forward_sweeps::<M, true, false>(model, decay, att, w, h, r, diag),
+        Kernel::Sixteen => forward_sweeps::<M, true, true>(model, decay, att, w, h, r, diag),
+    }
+}
+
+/// Reverse pass with the sweeps specialised for the kernel and decay model of `s`
+pub(crate) fn run_reverse_sweeps<M: DecayModel>(
+    s: &Sweeping<M>,
+    decay: &[f32],
+    att: &mut [f32],
+    w: usize,
+    h: usize,
+    r: Region,
+) {
+    let (model, diag) = (&s.model, s.diagonal_decay_mult);
+    match s.kernel {
+        Kernel::Four => reverse_sweeps::<M, false, false>(model, decay, att, w, h, r, diag),
+        Kernel::Eight => reverse_sweeps::<M, true, false>(model, decay, att, w, h, r, diag),
+        Kernel::Sixteen => reverse_sweeps::<M, true, true>(model, decay, att, w, h, r, diag),
+    }
+}
@@ -0,0 +1,110 @@
+//! Area and line light sources.
+//!
+//! Because the sweep is a max-propagation, an extended source is just every one of
+//! its cells seeded into the same grid. Each cell emits the full intensity, i.e.
+//! the intensity is a per-cell brightness: a long neon tube is as bright along
+//! its length as a short one, and lights a larger area rather than a brighter one.
+
+use super::model::DecayModel;
+use super::sweeping::Sweeping;
+
+/// Shape of a light source, anchored at the light's position.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Shape {
+    /// A single cell at the anchor
+    #[default]
+    Point,
+    /// A line segment from the anchor to `to` (inclusive, 8-connected)
+    Line { to: (usize, usize) },
+    /// A filled `width x height` rectangle with its top-left cell at the anchor
+    Rect { width: usize, height: usize },
+    /// An arbitrary `width`-wide stamp (row-major) with its top-left cell at the anchor.
+    /// A zero `width` covers no cells.
+    Mask { width: usize, cells: Vec<bool> },
+}
+
+impl Shape {
+    /// Cells covered by the shape anchored at `(x, y)`, clipped to the grid.
+    pub fn cells(&self, x: usize, y: usize, grid_width: usize, grid_height: usize) -> Vec<(usize, usize)> {
+        let inside = |&(cx, cy): &(usize, usize)| cx < grid_width && cy < grid_height;
+        match self {
+            Shape::Point => [(x, y)].into_iter().filter(inside).collect(),
+            Shape::Line { to } => line_cells((x, y), *to).into_iter().filter(inside).collect(),
+            Shape::Rect { width, height } => (y..y.saturating_add(*height).min(grid_height))
+                .flat_map(|cy| (x..x.saturating_add(*width).min(grid_width)).map(move |cx| (cx, cy)))
+                .collect(),
+            Shape::Mask { width: 0, .. } => Vec::new(),
+            Shape::Mask { width, cells } => cells
+                .iter()
+                .enumerate()
+                .filter(|&(_, &lit)| lit)
+                .map(|(i, _)| (x + i % width, y + i / width))
+                .filter(inside)
+                .collect(),
+        }
+    }
+
+    /// `(x, y, intensity)` seeds for the shape anchored at `(x, y)`.
+    pub fn lights(
+        &self,
+        x: usize,
+        y: usize,
+        grid_width: usize,
+        grid_height: usize,
+        intensity: f32,
+    ) -> Vec<(usize, usize, f32)> {
+        self.cells(x, y, grid_width, grid_height)
+            .into_iter()
+            .map(|(cx, cy)| (cx, cy, intensity))
+            .collect()
+    }
+}
+
+/// Bresenham line from `a` to `b`, both ends included
+pub(super) fn line_cells(a: (usize, usize), b: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x, mut y) = (a.0 as isize, a.1 as isize);
+    let (x1, y1) = (b.0 as isize, b.1 as isize);
+    let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+    let (sx, sy) = ((x1 - x).signum(), (y1 - y).signum());
+    let mut err = dx + dy;
+
+    let mut cells = Vec::with_capacity((dx.max(-dy) + 1) as usize);
+    loop {
+        cells.push((x as usize, y as usize));
+        if x == x1 && y == y1 {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+    cells
+}
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Calculate attenuation for an extended light source anchored at `(x, y)`.
+    ///
+    /// Every cell of `shape` is seeded at `intensity` and the whole source is
+    /// propagated in a single [`calculate_many`](Self::calculate_many) pass. Cells
+    /// of the shape outside the grid are ignored.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_shape(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+        shape: &Shape,
+        intensity: f32,
+    ) -> Vec<f32> {
+        let lights = shape.lights(x, y, width, height, intensity);
+        self.calculate_many(decay_flat, width, height, &lights)
+    }
+}
@@ -0,0 +1,91 @@
+//! Sky light for side-view worlds.
+//!
+//! Sunlight enters from the top row (or from cells flagged as open to the sky),
+//! falls straight down each column with its own falloff, and the regular sweep
+//! then spreads it sideways into caves and overhangs.
+
+use super::model::DecayModel;
+use super::region::Region;
+use super::sweeping::Sweeping;
+
+/// Ambient sunlight configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkyLight {
+    /// Sky brightness at full day (0.0-1.0)
+    pub intensity: f32,
+    /// Sky colour (RGB 0.0-1.0), used by [`apply_sky_light`](crate::color::apply_sky_light)
+    pub color: (f32, f32, f32),
+    /// Time-of-day scale for `intensity` (1.0 = noon, 0.0 = night)
+    pub day_factor: f32,
+    /// Distance multiplier for the decay model while falling straight down
+    /// (1.0 = same as the sweep, lower values let sunlight reach deeper down shafts)
+    pub falloff: f32,
+    /// Cells open to the sky (row-major). `None` means only the top row.
+    pub open: Option<Vec<bool>>,
+}
+
+impl Default for SkyLight {
+    fn default() -> Self {
+        SkyLight {
+            intensity: 1.0,
+            color: (1.0, 1.0, 1.0),
+            day_factor: 1.0,
+            falloff: 1.0,
+            open: None,
+        }
+    }
+}
+
+impl SkyLight {
+    /// White sky light of the given intensity entering along the top row.
+    pub fn new(intensity: f32) -> Self {
+        SkyLight { intensity, ..Self::default() }
+    }
+
+    /// Effective sky brightness after the day-time factor.
+    pub fn level(&self) -> f32 {
+        self.intensity * self.day_factor.max(0.0)
+    }
+
+    /// Whether cell `(x, y)` receives direct sky light.
+    fn is_open(&self, x: usize, y: usize, width: usize) -> bool {
+        match &self.open {
+            Some(open) => open[y * width + x],
+            None => y == 0,
+        }
+    }
+}
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Calculate sky light: a downward pass per column followed by a full sweep.
+    ///
+    /// Each open cell starts at [`SkyLight::level`]. Going down a column, each
+    /// cell passes light on through the decay model with `sky.falloff` as the
+    /// distance multiplier (`1 - decay * falloff` for [`Linear`](super::Linear)),
+    /// so it pours down shafts and stops at solid ground. The columns then seed a regular
+    /// forward + reverse sweep that carries the light sideways.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat); `sky.open`, if
+    /// set, must have `width * height` cells.
+    pub fn calculate_sky(&self, decay_flat: &[f32], width: usize, height: usize, sky: &SkyLight) -> Vec<f32> {
+        let level = sky.level();
+        let mut seed = vec![0.0f32; width * height];
+        if level <= 0.0 {
+            return seed;
+        }
+
+        for x in 0..width {
+            let mut light = 0.0f32;
+            for y in 0..height {
+                let idx = y * width + x;
+                if sky.is_open(x, y, width) {
+                    light = light.max(level);
+                }
+                seed[idx] = light;
+                light = self.model.transmit(light, decay_flat[idx], sky.falloff);
+            }
+        }
+
+        self.sweep_seeded(decay_flat, width, height, Region::full(width, height), seed)
+    }
+}
@@ -0,0 +1,120 @@
+//! Directional spot lights.
+//!
+//! A spot light is a point light restricted to a [`Cone`]. Cells that do not
+//! overlap the cone are swept as opaque, so light can only travel inside it and
+//! cannot leak around a wall back into the cone; walls inside the cone still
+//! cast shadows. The cone factor is then applied per cell for the soft edge.
+
+use super::model::DecayModel;
+use super::sweeping::Sweeping;
+
+/// Cone of a spot light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cone {
+    /// Direction the light points in, in radians (0 = +x, π/2 = +y, i.e. down)
+    pub direction: f32,
+    /// Angle from the axis to the edge of the cone, in radians
+    pub half_angle: f32,
+    /// Fraction of `half_angle` over which the edge fades out (0 = hard edge, 1 = fade from the axis)
+    pub softness: f32,
+}
+
+impl Cone {
+    /// Create a cone pointing at `direction` radians.
+    pub fn new(direction: f32, half_angle: f32, softness: f32) -> Self {
+        Cone { direction, half_angle, softness }
+    }
+
+    /// Brightness factor (0.0-1.0) for a cell at offset `(dx, dy)` from the source.
+    ///
+    /// The source cell itself is always fully lit.
+    pub fn factor(&self, dx: f32, dy: f32) -> f32 {
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return 1.0;
+        }
+
+        let (sin, cos) = self.direction.sin_cos();
+        let cos_angle = (dx * cos + dy * sin) / len;
+        let cos_outer = self.half_angle.cos();
+        let cos_inner = (self.half_angle * (1.0 - self.softness.clamp(0.0, 1.0))).cos();
+
+        if cos_angle >= cos_inner {
+            1.0
+        } else if cos_angle < cos_outer || cos_inner <= cos_outer {
+            0.0
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+
+    /// Multiply every cell of a flat attenuation grid by the cone factor for a source at `(x, y)`.
+    pub fn apply(&self, attenuation: &mut [f32], width: usize, x: usize, y: usize) {
+        for (i, att) in attenuation.iter_mut().enumerate() {
+            if *att > 0.0 {
+                let dx = (i % width) as f32 - x as f32;
+                let dy = (i / width) as f32 - y as f32;
+                *att *= self.factor(dx, dy);
+            }
+        }
+    }
+
+    /// Whether the cell at offset `(dx, dy)` overlaps the cone.
+    ///
+    /// A cell counts if a disc of radius 0.5 around its centre touches the cone,
+    /// i.e. its direction is within `asin(0.5 / len)` of the cone's edge. Every
+    /// column (or row) crossed by the axis then has an overlapping cell next to
+    /// the previous one, so even cones narrower than a cell stay 8-connected
+    /// to the source whatever their direction.
+    fn overlaps(&self, dx: f32, dy: f32) -> bool {
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return true;
+        }
+        let (sin, cos) = self.direction.sin_cos();
+        let angle = ((dx * cos + dy * sin) / len).clamp(-1.0, 1.0).acos();
+        angle <= self.half_angle + (0.5 / len).min(1.0).asin()
+    }
+
+    /// Copy of a flat grid with every cell outside the cone of a source at
+    /// `(x, y)` replaced by `opaque`, so a sweep cannot carry light through it.
+    pub(crate) fn occlude<T: Copy>(&self, decay: &[T], width: usize, x: usize, y: usize, opaque: T) -> Vec<T> {
+        decay
+            .iter()
+            .enumerate()
+            .map(|(i, &d)| {
+                let dx = (i % width) as f32 - x as f32;
+                let dy = (i / width) as f32 - y as f32;
+                if self.overlaps(dx, dy) { d } else { opaque }
+            })
+            .collect()
+    }
+}
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Calculate attenuation for a spot light at `(light_x, light_y)`.
+    ///
+    /// Light is propagated as for [`calculate_flat`](Self::calculate_flat) with
+    /// every cell that does not overlap `cone` treated as opaque (infinite decay), then scaled
+    /// by the cone factor. Cells behind the light or outside the cone stay dark,
+    /// and walls inside the cone cast shadows light cannot bend around.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    #[allow(clippy::too_many_arguments)]
+    pub fn calculate_spot(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        light_x: usize,
+        light_y: usize,
+        source_intensity: f32,
+        cone: &Cone,
+    ) -> Vec<f32> {
+        let decay = cone.occlude(decay_flat, width, light_x, light_y, f32::INFINITY);
+        let mut attenuation = self.calculate_flat(&decay, width, height, light_x, light_y, source_intensity);
+        cone.apply(&mut attenuation, width, light_x, light_y);
+        attenuation
+    }
+}
@@ -5,8 +5,68 @@
 //!
 //! Key optimizations:
 //! - Flat Vec<f32> for better cache locality
-//! - Parallel forward/reverse passes using rayon
-//! - Hand-unrolled sweep loops for maximum performance
+//! - Parallel forward/reverse passes using rayon (`parallel` feature), and a
+//!   column-strip wavefront inside each sweep of a large region
+//! - Row-wise sweeps: vectorised pulls from finished rows, scalar scan along the row
+
+use super::error::{LightError, validate_decay, validate_source};
+use super::model::{DecayModel, Linear};
+use super::region::Region;
+
+/// Neighbourhood a sweep propagates light through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Kernel {
+    /// Orthogonal neighbours only (Manhattan distance, diamond-shaped falloff)
+    Four,
+    /// Orthogonal and diagonal neighbours (octagonal falloff)
+    #[default]
+    Eight,
+    /// Eight neighbours plus knight moves at √5 cost (rounder falloff)
+    Sixteen,
+}
+
+impl Kernel {
+    /// Neighbour offsets `(dx, dy, decay multiplier)`, with diagonal steps
+    /// charged `diag`
+    pub(super) fn steps(self, diag: f32) -> Vec<(isize, isize, f32)> {
+        let mut steps = vec![(1, 0, 1.0), (-1, 0, 1.0), (0, 1, 1.0), (0, -1, 1.0)];
+        if self != Kernel::Four {
+            steps.extend([(1, 1, diag), (1, -1, diag), (-1, 1, diag), (-1, -1, diag)]);
+        }
+        if self == Kernel::Sixteen {
+            for (dx, dy) in [(2, 1), (1, 2), (-1, 2), (-2, 1), (-2, -1), (-1, -2), (1, -2), (2, -1)] {
+                steps.push((dx, dy, KNIGHT_DECAY_MULT));
+            }
+        }
+        steps
+    }
+}
+
+/// How light behaves at the edges of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Edges are hard boundaries (no light leaves or enters the grid)
+    #[default]
+    Clamp,
+    /// The left and right edges are joined (cylinder, e.g. planet maps)
+    WrapX,
+    /// The top and bottom edges are joined
+    WrapY,
+    /// Both pairs of edges are joined (torus)
+    WrapBoth,
+}
+
+impl Boundary {
+    /// Whether the left and right edges are joined.
+    pub fn wraps_x(self) -> bool {
+        matches!(self, Boundary::WrapX | Boundary::WrapBoth)
+    }
+
+    /// Whether the top and bottom edges are joined.
+    pub fn wraps_y(self) -> bool {
+        matches!(self, Boundary::WrapY | Boundary::WrapBoth)
+    }
+}
 
 /// Sweeping neighbor attenuation algorithm.
 ///
@@ -14,17 +74,47 @@
 /// - Forward pass: TL→BR, BR→TL, Down, Up
 /// - Reverse pass: Up, Down, BR→TL, TL→BR
 /// - Merge: max(forward, reverse) for each cell
+///
+/// Generic over the per-step transfer function `M` (default: [`Linear`]).
 #[derive(Debug, Clone)]
-pub struct Sweeping {
+pub struct Sweeping<M: DecayModel = Linear> {
     /// Multiplier for diagonal distance (default: √2 ≈ 1.414)
     pub diagonal_decay_mult: f32,
+    /// Light below this level is treated as dark (default: 1e-4). Sweeps are
+    /// restricted to the window where a light can stay above it, and cells
+    /// outside stay at 0. Set to 0.0 to always sweep the whole grid.
+    pub epsilon: f32,
+    /// Neighbourhood used for propagation (default: [`Kernel::Eight`])
+    pub kernel: Kernel,
+    /// Per-step transfer function (see [`DecayModel`])
+    pub model: M,
+    /// Edge behaviour (default: [`Boundary::Clamp`])
+    pub boundary: Boundary,
 }
 
-impl Default for Sweeping {
+/// Result of [`Sweeping::calculate_converged`].
+#[derive(Debug, Clone)]
+pub struct Convergence {
+    /// Combined attenuation (row-major: index = y * width + x)
+    pub attenuation: Vec<f32>,
+    /// Number of forward + reverse rounds that were run
+    pub iterations: usize,
+    /// Whether the last round left every cell unchanged (false if the cap was hit)
+    pub converged: bool,
+}
+
+/// Multiplier for knight-move distance in [`Kernel::Sixteen`] (√5)
+pub(super) const KNIGHT_DECAY_MULT: f32 = 2.236_068;
+
+/// Radius (in cells) of the patch around a subpixel light that is blended exactly
+const SUBPIXEL_PATCH_RADIUS: usize = 2;
+
+/// Default light cutoff for [`Sweeping::epsilon`]
+pub const DEFAULT_EPSILON: f32 = 1e-4;
+
+impl<M: DecayModel + Default> Default for Sweeping<M> {
     fn default() -> Self {
-        Sweeping {
-            diagonal_decay_mult: std::f32::consts::SQRT_2,
-        }
+        Sweeping::with_model(M::default())
     }
 }
 
@@ -36,13 +126,55 @@ impl Sweeping {
 
     /// Create a new Sweeping algorithm with custom diagonal multiplier.
     pub fn with_diagonal_mult(diagonal_decay_mult: f32) -> Self {
-        Sweeping { diagonal_decay_mult }
+        Sweeping { diagonal_decay_mult, ..Self::default() }
+    }
+
+    /// Create a new Sweeping algorithm using the given neighbourhood kernel.
+    pub fn with_kernel(kernel: Kernel) -> Self {
+        Sweeping { kernel, ..Self::default() }
+    }
+}
+
+impl<M: DecayModel> Sweeping<M> {
+    /// Create a new Sweeping algorithm with default settings and the given decay model.
+    pub fn with_model(model: M) -> Self {
+        Sweeping {
+            diagonal_decay_mult: std::f32::consts::SQRT_2,
+            epsilon: DEFAULT_EPSILON,
+            kernel: Kernel::Eight,
+            model,
+            boundary: Boundary::Clamp,
+        }
+    }
+
+    /// Checked variant of [`calculate_flat`](Self::calculate_flat).
+    ///
+    /// Returns a [`LightError`] instead of panicking or producing garbage when the
+    /// grid is empty, `decay_flat` has the wrong length, the light source is out of
+    /// bounds, or a decay value is NaN, infinite or negative.
+    pub fn try_calculate_flat(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        light_x: usize,
+        light_y: usize,
+        source_intensity: f32,
+    ) -> Result<Vec<f32>, LightError> {
+        validate_decay(decay_flat, width, height)?;
+        validate_source(light_x, light_y, width, height)?;
+        Ok(self.calculate_flat(decay_flat, width, height, light_x, light_y, source_intensity))
     }
 
     /// Calculate attenuation with flat memory layout.
     /// Returns a flat Vec<f32> with row-major order: index = y * width + x
     /// 
     /// `source_intensity` controls the initial brightness at the light source (0.0-1.0)
+    ///
+    /// This is the unchecked fast path: inputs are not validated. It panics if the
+    /// source is out of bounds or `decay_flat.len() != width * height`, and gives
+    /// meaningless results for NaN or negative decay. Use
+    /// [`try_calculate_flat`](Self::try_calculate_flat) for untrusted input.
     pub fn calculate_flat(
         &self,
         decay_flat: &[f32],
@@ -52,31 +184,346 @@ impl Sweeping {
         light_y: usize,
         source_intensity: f32,
     ) -> Vec<f32> {
-        let diag = self.diagonal_decay_mult;
-        let light_idx = light_y * width + light_x;
-
-        // Run forward and reverse passes in parallel using rayon
-        let (mut forward, reverse) = rayon::join(
-            || {
-                let mut grid = vec![0.0f32; width * height];
-                grid[light_idx] = source_intensity;
-                run_forward_sweeps(decay_flat, &mut grid, width, height, diag);
-                grid
-            },
-            || {
-                let mut grid = vec![0.0f32; width * height];
-                grid[light_idx] = source_intensity;
-                run_reverse_sweeps(decay_flat, &mut grid, width, height, diag);
-                grid
-            },
-        );
+        let mut seed = vec![0.0f32; width * height];
+        seed[light_y * width + light_x] = source_intensity;
+        let window = self.light_window(decay_flat, width, height, &[(light_x, light_y, source_intensity)]);
+        self.sweep_seeded(decay_flat, width, height, window, seed)
+    }
+
+    /// Checked variant of [`calculate_many`](Self::calculate_many).
+    pub fn try_calculate_many(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+    ) -> Result<Vec<f32>, LightError> {
+        validate_decay(decay_flat, width, height)?;
+        for &(x, y, _) in lights {
+            validate_source(x, y, width, height)?;
+        }
+        Ok(self.calculate_many(decay_flat, width, height, lights))
+    }
 
-        // Merge with max
-        for i in 0..forward.len() {
-            forward[i] = forward[i].max(reverse[i]);
+    /// Calculate the combined attenuation of several point lights in one pass.
+    ///
+    /// `lights` holds `(x, y, intensity)` triples. Because the sweep is a
+    /// max-propagation, seeding every source into the same grid gives exactly
+    /// the per-cell maximum of the individual `calculate_flat` results, at the
+    /// cost of a single forward + reverse pass regardless of light count.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    pub fn calculate_many(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+    ) -> Vec<f32> {
+        let mut att = vec![0.0f32; width * height];
+        self.calculate_many_into(decay_flat, width, height, lights, &mut att);
+        att
+    }
+
+    /// [`calculate_many`](Self::calculate_many) into `out`: seeds the lights
+    /// there and sweeps it in place (`out.len() == width * height`).
+    pub(super) fn calculate_many_into(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        out.fill(0.0);
+        for &(x, y, intensity) in lights {
+            let idx = y * width + x;
+            out[idx] = out[idx].max(intensity);
         }
+        let window = self.light_window(decay_flat, width, height, lights);
+        self.sweep_seeded_into(decay_flat, width, height, window, out);
+    }
+
+    /// Like [`calculate_many`](Self::calculate_many), but repeats the forward +
+    /// reverse rounds until no cell changes or `max_iterations` rounds have run.
+    ///
+    /// One round carries light around a limited number of turns, so winding
+    /// corridors (spirals, mazes) come out too dark with a single round. Each
+    /// further round starts from the previous result and can only brighten cells,
+    /// so once converged the result is the exact best path (see
+    /// [`BestPath`](super::reference::BestPath)). At least one round is always run.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    pub fn calculate_converged(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+        max_iterations: usize,
+    ) -> Convergence {
+        let mut attenuation = vec![0.0f32; width * height];
+        for &(x, y, intensity) in lights {
+            let idx = y * width + x;
+            attenuation[idx] = attenuation[idx].max(intensity);
+        }
+        let window = self.light_window(decay_flat, width, height, lights);
+
+        let mut iterations = 0;
+        while iterations < max_iterations.max(1) {
+            iterations += 1;
+            let next = self.sweep_seeded(decay_flat, width, height, window, attenuation.clone());
+            let changed = next != attenuation;
+            attenuation = next;
+            if !changed {
+                return Convergence { attenuation, iterations, converged: true };
+            }
+        }
+
+        Convergence { attenuation, iterations, converged: false }
+    }
+
+    /// Calculate attenuation for a light at a fractional position in one sweep.
+    ///
+    /// `(fx, fy)` is in cell units, with cell `(x, y)` at integer coordinates. The
+    /// four surrounding cells are treated as bilinearly weighted lights, but only
+    /// within a small patch around them: there the four per-corner grids are
+    /// computed and blended exactly, and the blended patch then seeds a single
+    /// sweep over the whole grid. This replaces blending four full
+    /// `calculate_flat` grids at roughly a quarter of the cost.
+    ///
+    /// In open space the result matches the four-grid blend. Near walls it can be
+    /// up to ~0.15 × `intensity` brighter (the seeded patch finds routes the four
+    /// fixed sweep schedules miss) or ~0.03 × `intensity` darker (corners whose
+    /// light takes different routes are max-combined rather than summed); see
+    /// `test_subpixel_matches_four_grid_blend`.
+    ///
+    /// Positions are clamped to the grid. Unchecked like
+    /// [`calculate_flat`](Self::calculate_flat) for the grid itself.
+    pub fn calculate_subpixel(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        fx: f32,
+        fy: f32,
+        intensity: f32,
+    ) -> Vec<f32> {
+        let x0 = (fx.max(0.0).floor() as usize).min(width - 1);
+        let y0 = (fy.max(0.0).floor() as usize).min(height - 1);
+        let x1 = (x0 + 1).min(width - 1);
+        let y1 = (y0 + 1).min(height - 1);
+        let tx = (fx - x0 as f32).clamp(0.0, 1.0);
+        let ty = (fy - y0 as f32).clamp(0.0, 1.0);
+
+        let corners = [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            (x1, y0, tx * (1.0 - ty)),
+            (x0, y1, (1.0 - tx) * ty),
+            (x1, y1, tx * ty),
+        ];
+
+        // Exact four-grid blend inside a small patch around the corners
+        let patch = Region::around(x0, y0, SUBPIXEL_PATCH_RADIUS, width, height)
+            .union(&Region::around(x1, y1, SUBPIXEL_PATCH_RADIUS, width, height));
+        let (pw, ph) = (patch.width(), patch.height());
+        let mut patch_decay = Vec::with_capacity(pw * ph);
+        for y in patch.y0..patch.y1 {
+            patch_decay.extend_from_slice(&decay_flat[y * width + patch.x0..y * width + patch.x1]);
+        }
+
+        let local = Sweeping { epsilon: 0.0, boundary: Boundary::Clamp, ..self.clone() };
+        let mut blended = vec![0.0f32; pw * ph];
+        for &(cx, cy, weight) in &corners {
+            if weight == 0.0 {
+                continue;
+            }
+            let grid = local.calculate_flat(&patch_decay, pw, ph, cx - patch.x0, cy - patch.y0, intensity);
+            for (b, g) in blended.iter_mut().zip(&grid) {
+                *b += weight * g;
+            }
+        }
+
+        // Seed the blended patch and sweep the whole grid once
+        let lights: Vec<(usize, usize, f32)> = blended
+            .iter()
+            .enumerate()
+            .filter(|&(_, &v)| v > 0.0)
+            .map(|(i, &v)| (patch.x0 + i % pw, patch.y0 + i / pw, v))
+            .collect();
+        self.calculate_many(decay_flat, width, height, &lights)
+    }
+
+    /// Calculate per-channel (RGB) light for materials that tint what passes through.
+    ///
+    /// `decay_rgb` holds one `[r, g, b]` decay per cell (row-major), so a red glass
+    /// cell can use e.g. `[0.05, 0.9, 0.9]` to pass red while absorbing green and
+    /// blue. `source_rgb` is the light's color scaled by its intensity. Each channel
+    /// follows the same propagation rule as [`calculate_flat`](Self::calculate_flat),
+    /// and the result is an RGB light map with `index = y * width + x`.
+    ///
+    /// Unchecked like [`calculate_flat`](Self::calculate_flat).
+    pub fn calculate_rgb(
+        &self,
+        decay_rgb: &[[f32; 3]],
+        width: usize,
+        height: usize,
+        light_x: usize,
+        light_y: usize,
+        source_rgb: [f32; 3],
+    ) -> Vec<[f32; 3]> {
+        self.calculate_rgb_many(decay_rgb, width, height, &[(light_x, light_y, source_rgb)])
+    }
+
+    /// Per-channel variant of [`calculate_many`](Self::calculate_many) for
+    /// `(x, y, source_rgb)` lights.
+    ///
+    /// All three channels are swept together, in one forward and one reverse
+    /// pass. They share a light window sized for the brightest channel and the
+    /// least-decaying one, so a channel can pick up faint light (below
+    /// `epsilon`) a little beyond the window it would get on its own.
+    pub fn calculate_rgb_many(
+        &self,
+        decay_rgb: &[[f32; 3]],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, [f32; 3])],
+    ) -> Vec<[f32; 3]> {
+        let mut seed = vec![[0.0f32; 3]; width * height];
+        for &(x, y, rgb) in lights {
+            let idx = y * width + x;
+            seed[idx] = seed[idx].max(rgb);
+        }
+        let min_decay = decay_rgb.iter().fold(f32::INFINITY, |m, d| m.min(d.least()));
+        let peaks: Vec<_> = lights.iter().map(|&(x, y, rgb)| (x, y, rgb.peak())).collect();
+        let window = self.window_for(min_decay, width, height, &peaks);
+        self.sweep_seeded(decay_rgb, width, height, window, seed)
+    }
+
+    /// Bounding box of the cells where any of `lights` can exceed `epsilon`.
+    ///
+    /// Every step loses at least as much light as an orthogonal step through the
+    /// least-decaying cell, so [`DecayModel::reach`] with the grid's minimum decay
+    /// bounds how far (in Chebyshev distance) each light can stay above `epsilon`.
+    /// For [`Linear`] that is `ln(epsilon / i) / ln(1 - min_decay)` cells.
+    pub fn light_window(
+        &self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+    ) -> Region {
+        let min_decay = decay_flat.iter().copied().fold(f32::INFINITY, f32::min);
+        self.window_for(min_decay, width, height, lights)
+    }
+
+    /// [`light_window`](Self::light_window) for a grid whose minimum decay is known
+    pub(super) fn window_for(&self, min_decay: f32, width: usize, height: usize, lights: &[(usize, usize, f32)]) -> Region {
+        let mut window = Region::empty();
+        for &(x, y, intensity) in lights {
+            if let Some(radius) = self.model.reach(intensity, min_decay, self.epsilon) {
+                window = window.union(&Region::around(x, y, radius, width, height));
+            }
+        }
+        window
+    }
+
+    /// Run the forward and reverse passes over a pre-seeded grid and merge them.
+    /// Only cells inside `region` are swept; the rest keep their seed values.
+    ///
+    /// With a wrapping [`Boundary`] the whole grid is swept instead (see
+    /// [`sweep_wrapped`](Self::sweep_wrapped)); `region` only tells whether
+    /// there is any light at all.
+    pub(super) fn sweep_seeded<T: Channels>(
+        &self,
+        decay_flat: &[T],
+        width: usize,
+        height: usize,
+        region: Region,
+        mut seed: Vec<T>,
+    ) -> Vec<T> {
+        self.sweep_seeded_into(decay_flat, width, height, region, &mut seed);
+        seed
+    }
+
+    /// [`sweep_seeded`](Self::sweep_seeded) in place over the seeded grid `att`
+    pub(super) fn sweep_seeded_into<T: Channels>(
+        &self,
+        decay_flat: &[T],
+        width: usize,
+        height: usize,
+        region: Region,
+        att: &mut [T],
+    ) {
+        if region.is_empty() {
+            return;
+        }
+        if self.boundary != Boundary::Clamp {
+            return self.sweep_wrapped(decay_flat, width, height, att);
+        }
+        self.sweep_clamped(decay_flat, width, height, region, att);
+    }
+
+    /// Sweep a wrapping grid by padding it with a halo copied from the opposite
+    /// edges, so light crossing the seam is swept like any other light.
+    ///
+    /// The halo on each wrapped axis is as wide as the brightest seed can reach
+    /// above `epsilon`, capped at the grid size (three tiles). Paths that cross
+    /// the same seam more than once are therefore not followed.
+    fn sweep_wrapped<T: Channels>(&self, decay_flat: &[T], width: usize, height: usize, att: &mut [T]) {
+        let peak = att.iter().fold(0.0f32, |p, v| p.max(v.peak()));
+        let min_decay = decay_flat.iter().fold(f32::INFINITY, |m, d| m.min(d.least()));
+        let halo = self
+            .model
+            .reach(peak, min_decay, self.epsilon)
+            .map_or(0, |steps| steps.saturating_add(1));
+        let pad_x = if self.boundary.wraps_x() { halo.min(width) } else { 0 };
+        let pad_y = if self.boundary.wraps_y() { halo.min(height) } else { 0 };
+        let (pw, ph) = (width + 2 * pad_x, height + 2 * pad_y);
+
+        // Padded cell (px, py) shows grid cell ((px - pad_x) mod width, (py - pad_y) mod height)
+        let source = |px: usize, py: usize| {
+            let x = (px + width - pad_x % width) % width;
+            let y = (py + height - pad_y % height) % height;
+            y * width + x
+        };
+        let mut padded_decay = Vec::with_capacity(pw * ph);
+        let mut padded_seed = Vec::with_capacity(pw * ph);
+        for py in 0..ph {
+            for px in 0..pw {
+                let i = source(px, py);
+                padded_decay.push(decay_flat[i]);
+                padded_seed.push(att[i]);
+            }
+        }
+
+        self.sweep_clamped(&padded_decay, pw, ph, Region::full(pw, ph), &mut padded_seed);
+
+        for y in 0..height {
+            let row = (y + pad_y) * pw + pad_x;
+            att[y * width..(y + 1) * width].copy_from_slice(&padded_seed[row..row + width]);
+        }
+    }
+
+    /// Forward + reverse sweeps over `region` with hard grid edges.
+    fn sweep_clamped<T: Channels>(
+        &self,
+        decay_flat: &[T],
+        width: usize,
+        height: usize,
+        region: Region,
+        att: &mut [T],
+    ) {
+        let mut reverse = att.to_vec();
+
+        // Run forward and reverse passes (in parallel when rayon is available)
+        join_passes(
+            &mut Default::default(),
+            |front| run_forward_sweeps(self, decay_flat, att, width, height, region, front),
+            |front| run_reverse_sweeps(self, decay_flat, &mut reverse, width, height, region, front),
+        );
 
-        forward
+        merge_max(att, &reverse, width, region);
     }
 }
 
@@ -84,104 +531,514 @@ impl Sweeping {
 // Propagation helper
 // ============================================================================
 
-/// Calculate light propagation from neighbor to current cell
+#[cfg(feature = "parallel")]
+pub(crate) use super::wavefront::Wavefront;
+
+/// Without the `parallel` feature every sweep is serial and needs no scratch
+#[cfg(not(feature = "parallel"))]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Wavefront;
+
+/// Run the forward and reverse passes, in parallel with rayon when the `parallel`
+/// feature is enabled. The pool's threads are split between the two passes'
+/// wavefronts so together they never start more workers than the pool has.
 #[inline]
-fn propagate(att: &[f32], decay: &[f32], ni: usize, mult: f32) -> f32 {
-    att[ni] * (1.0 - decay[ni] * mult)
+pub(super) fn join_passes<A, B>(fronts: &mut [Wavefront; 2], a: A, b: B)
+where
+    A: FnOnce(&mut Wavefront) + Send,
+    B: FnOnce(&mut Wavefront) + Send,
+{
+    let [front_a, front_b] = fronts;
+    #[cfg(feature = "parallel")]
+    {
+        let threads = rayon::current_num_threads();
+        front_a.workers = threads.div_ceil(2);
+        front_b.workers = (threads / 2).max(1);
+        rayon::join(|| a(front_a), || b(front_b));
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        a(front_a);
+        b(front_b);
+    }
+}
+
+/// Merge the reverse pass into the forward one: `att = max(att, reverse)` over `region`
+pub(super) fn merge_max<T: Channels>(att: &mut [T], reverse: &[T], width: usize, region: Region) {
+    for y in region.y0..region.y1 {
+        let cells = y * width + region.x0..y * width + region.x1;
+        for (a, &b) in att[cells.clone()].iter_mut().zip(&reverse[cells]) {
+            *a = a.max(b);
+        }
+    }
+}
+
+/// Number of steps light starting at `peak` can travel with per-step
+/// transmittance `t` while staying at or above `epsilon`. `None` if `peak` is
+/// already below `epsilon`; `usize::MAX` if light never fades (`t >= 1`).
+pub(super) fn reach(peak: f32, t: f32, epsilon: f32) -> Option<usize> {
+    if peak < epsilon {
+        return None;
+    }
+    if t >= 1.0 {
+        return Some(usize::MAX);
+    }
+    if t <= 0.0 {
+        return Some(0);
+    }
+    let steps = ((epsilon / peak).ln() / t.ln()).floor();
+    Some(if steps.is_finite() { steps as usize } else { usize::MAX })
 }
 
 // ============================================================================
-// Four sweep patterns (hand-unrolled for performance)
+// Row kernels
 // ============================================================================
+//
+// Sweeps visit a region one row at a time. Every neighbour of a cell except the
+// one just visited in the same row lies in a row that is already final, so each
+// row is processed in two steps:
+//
+// 1. pull: fold in light from the finished rows above (or below) with straight
+//    loops over row slices, which the compiler vectorises;
+// 2. scan: the serial dependency on the left (or right) neighbour.
+//
+// `max` does not care about order, so this matches visiting the cells one by
+// one bit for bit. Columns whose neighbour would fall off the grid are clipped
+// from each loop's range up front instead of being tested per cell.
+//
+// Kernels only borrow the columns they touch, so the wavefront sweeps can hand
+// different column strips of the same rows to different threads.
 
-/// Sweep from top-left to bottom-right, checking left/up/up-left neighbors
-#[inline]
-fn sweep_tl_to_br(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    for y in 0..h {
-        for x in 0..w {
-            let idx = y * w + x;
-            let mut max_prop = att[idx];
-            if x > 0 {
-                max_prop = max_prop.max(propagate(att, decay, idx - 1, 1.0));
-            }
-            if y > 0 {
-                max_prop = max_prop.max(propagate(att, decay, idx - w, 1.0));
-            }
-            if x > 0 && y > 0 {
-                max_prop = max_prop.max(propagate(att, decay, idx - w - 1, diag));
-            }
-            att[idx] = max_prop;
-        }
+/// Values the sweeps carry per cell: one light level, or one per RGB channel.
+///
+/// Decay is stored the same way, and each channel is stepped exactly like a
+/// scalar sweep, so fusing channels does not change any of them.
+pub(crate) trait Channels: Copy + Send + Sync {
+    /// Per-channel maximum
+    fn max(self, other: Self) -> Self;
+    /// [`DecayModel::transmit`] for each channel
+    fn transmit<M: DecayModel>(model: &M, light: Self, decay: Self, mult: f32) -> Self;
+    /// Decay charged to a knight move: the larger of `self` and the average of
+    /// `a` and `b`, per channel
+    fn knight_decay(self, a: Self, b: Self) -> Self;
+    /// Brightest channel
+    fn peak(self) -> f32;
+    /// Least-decaying channel
+    fn least(self) -> f32;
+}
+
+impl Channels for f32 {
+    #[inline(always)]
+    fn max(self, other: f32) -> f32 {
+        f32::max(self, other)
+    }
+
+    #[inline(always)]
+    fn transmit<M: DecayModel>(model: &M, light: f32, decay: f32, mult: f32) -> f32 {
+        model.transmit(light, decay, mult)
+    }
+
+    #[inline(always)]
+    fn knight_decay(self, a: f32, b: f32) -> f32 {
+        f32::max(self, 0.5 * (a + b))
+    }
+
+    fn peak(self) -> f32 {
+        self
+    }
+
+    fn least(self) -> f32 {
+        self
     }
 }
 
-/// Sweep from bottom-right to top-left, checking right/down/down-right neighbors
-#[inline]
-fn sweep_br_to_tl(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    for y in (0..h).rev() {
-        for x in (0..w).rev() {
-            let idx = y * w + x;
-            let mut max_prop = att[idx];
-            if x + 1 < w {
-                max_prop = max_prop.max(propagate(att, decay, idx + 1, 1.0));
-            }
-            if y + 1 < h {
-                max_prop = max_prop.max(propagate(att, decay, idx + w, 1.0));
-            }
-            if x + 1 < w && y + 1 < h {
-                max_prop = max_prop.max(propagate(att, decay, idx + w + 1, diag));
-            }
-            att[idx] = max_prop;
+impl Channels for [f32; 3] {
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        [self[0].max(other[0]), self[1].max(other[1]), self[2].max(other[2])]
+    }
+
+    #[inline(always)]
+    fn transmit<M: DecayModel>(model: &M, light: Self, decay: Self, mult: f32) -> Self {
+        [
+            model.transmit(light[0], decay[0], mult),
+            model.transmit(light[1], decay[1], mult),
+            model.transmit(light[2], decay[2], mult),
+        ]
+    }
+
+    #[inline(always)]
+    fn knight_decay(self, a: Self, b: Self) -> Self {
+        [self[0].knight_decay(a[0], b[0]), self[1].knight_decay(a[1], b[1]), self[2].knight_decay(a[2], b[2])]
+    }
+
+    fn peak(self) -> f32 {
+        self[0].max(self[1]).max(self[2])
+    }
+
+    fn least(self) -> f32 {
+        self[0].min(self[1]).min(self[2])
+    }
+}
+
+/// How the row kernels turn a neighbour's light into light arriving over a step
+pub(super) trait Steps<T: Channels = f32>: Sync {
+    /// Orthogonal step; `f` is the source cell's [`Factors::straight`] entry
+    fn straight(&self, light: T, f: T) -> T;
+    /// Diagonal step; `f` is the source cell's [`Factors::diagonal`] entry
+    fn diagonal(&self, light: T, f: T) -> T;
+    /// Knight move charged `decay`
+    fn knight(&self, light: T, decay: T) -> T;
+}
+
+/// Steps computed from raw decay values on the fly ([`Factors::decay_only`])
+pub(super) struct DecaySteps<'a, M> {
+    pub(super) model: &'a M,
+    pub(super) diag: f32,
+}
+
+impl<M: DecayModel, T: Channels> Steps<T> for DecaySteps<'_, M> {
+    #[inline(always)]
+    fn straight(&self, light: T, decay: T) -> T {
+        T::transmit(self.model, light, decay, 1.0)
+    }
+
+    #[inline(always)]
+    fn diagonal(&self, light: T, decay: T) -> T {
+        T::transmit(self.model, light, decay, self.diag)
+    }
+
+    #[inline(always)]
+    fn knight(&self, light: T, decay: T) -> T {
+        T::transmit(self.model, light, decay, KNIGHT_DECAY_MULT)
+    }
+}
+
+/// Steps from precomputed [`DecayModel::factor`]s (a transmittance grid)
+pub(super) struct FactorSteps<'a, M>(pub(super) &'a M);
+
+impl<M: DecayModel> Steps for FactorSteps<'_, M> {
+    #[inline(always)]
+    fn straight(&self, light: f32, f: f32) -> f32 {
+        self.0.apply(light, f)
+    }
+
+    #[inline(always)]
+    fn diagonal(&self, light: f32, f: f32) -> f32 {
+        self.0.apply(light, f)
+    }
+
+    #[inline(always)]
+    fn knight(&self, light: f32, decay: f32) -> f32 {
+        self.0.transmit(light, decay, KNIGHT_DECAY_MULT)
+    }
+}
+
+/// Per-cell inputs of the row kernels, as whole grids or single rows
+#[derive(Clone, Copy)]
+pub(super) struct Factors<'a, T = f32> {
+    /// Passed to [`Steps::straight`] for light leaving each cell
+    pub(super) straight: &'a [T],
+    /// Passed to [`Steps::diagonal`] for light leaving each cell
+    pub(super) diagonal: &'a [T],
+    /// Raw decay, for knight moves
+    pub(super) decay: &'a [T],
+}
+
+impl<'a, T> Factors<'a, T> {
+    /// The decay grid for every input, for [`DecaySteps`]
+    pub(super) fn decay_only(decay: &'a [T]) -> Self {
+        Factors { straight: decay, diagonal: decay, decay }
+    }
+
+    /// Row `y` of `w`-wide grids
+    #[inline(always)]
+    pub(super) fn row(&self, y: usize, w: usize) -> Self {
+        let cells = y * w..(y + 1) * w;
+        Factors {
+            straight: &self.straight[cells.clone()],
+            diagonal: &self.diagonal[cells.clone()],
+            decay: &self.decay[cells],
         }
     }
 }
 
-/// Sweep top-down, checking left/up and both upper diagonals
-#[inline]
-fn sweep_down(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    for y in 0..h {
-        for x in 0..w {
-            let idx = y * w + x;
-            let mut max_prop = att[idx];
-            if x > 0 {
-                max_prop = max_prop.max(propagate(att, decay, idx - 1, 1.0));
+/// Columns per block of a sweep row. Pulling and scanning in short blocks lets
+/// the CPU overlap the vector pulls of one block with the serial scan of the
+/// previous one, and keeps the rows involved in L1.
+const COLUMN_BLOCK: usize = 32;
+
+/// Split columns `x0..x1` into `COLUMN_BLOCK`-wide `(b0, b1)` ranges, left to right
+#[inline(always)]
+fn column_blocks(x0: usize, x1: usize) -> impl DoubleEndedIterator<Item = (usize, usize)> {
+    (x0..x1).step_by(COLUMN_BLOCK).map(move |b0| (b0, (b0 + COLUMN_BLOCK).min(x1)))
+}
+
+/// Columns `lo..hi` of `x0..x1` whose neighbour `x + dx` lies inside a row of
+/// width `w`, and that neighbour's column for `lo`
+#[inline(always)]
+fn clip(w: usize, x0: usize, x1: usize, dx: isize) -> Option<(usize, usize, usize)> {
+    let lo = x0.max(dx.min(0).unsigned_abs());
+    let hi = x1.min(w.saturating_sub(dx.max(0) as usize));
+    (lo < hi).then(|| (lo, hi, lo.wrapping_add_signed(dx)))
+}
+
+/// A finished row that a sweep row pulls light from: attenuation for columns
+/// `x..x + att.len()` and the row's full [`Factors`]
+#[derive(Clone, Copy)]
+pub(super) struct Source<'a, T = f32> {
+    pub(super) att: &'a [T],
+    pub(super) x: usize,
+    pub(super) rows: Factors<'a, T>,
+}
+
+/// `row[i] = max(row[i], step(src[x + dx], f[x + dx]))` for the cells of `row`
+/// (columns `x0..`) that have that neighbour; `f` is a full row
+#[inline(always)]
+fn pull<T: Channels>(step: impl Fn(T, T) -> T, row: &mut [T], x0: usize, src: Source<T>, f: &[T], dx: isize) {
+    let Some((lo, hi, s)) = clip(f.len(), x0, x0 + row.len(), dx) else { return };
+    let n = hi - lo;
+    let light = &src.att[s - src.x..s - src.x + n];
+    for ((out, &light), &f) in row[lo - x0..hi - x0].iter_mut().zip(light).zip(&f[s..s + n]) {
+        *out = out.max(step(light, f));
+    }
+}
+
+/// Knight-move [`pull`]. The move from `x + dx` in `src` passes between the
+/// cells `x + dx1` of decay row `dm1` and `x + dx2` of `dm2`, and is charged the
+/// larger of the source decay and their average decay; light cannot jump
+/// cleanly past a wall corner.
+#[inline(always)]
+fn pull_knight<T: Channels, S: Steps<T>>(
+    steps: &S,
+    row: &mut [T],
+    x0: usize,
+    src: Source<T>,
+    dm1: &[T],
+    dm2: &[T],
+    [dx, dx1, dx2]: [isize; 3],
+) {
+    let Some((lo, hi, s)) = clip(dm1.len(), x0, x0 + row.len(), dx) else { return };
+    let n = hi - lo;
+    let (a1, a2) = (lo.wrapping_add_signed(dx1), lo.wrapping_add_signed(dx2));
+    let cells = row[lo - x0..hi - x0]
+        .iter_mut()
+        .zip(&src.att[s - src.x..s - src.x + n])
+        .zip(&src.rows.decay[s..s + n])
+        .zip(&dm1[a1..a1 + n])
+        .zip(&dm2[a2..a2 + n]);
+    for ((((out, &light), &decay), &d1), &d2) in cells {
+        let d = decay.knight_decay(d1, d2);
+        *out = out.max(steps.knight(light, d));
+    }
+}
+
+/// Serial step left to right over `row` (columns `x0..`): light from the left
+/// neighbour. `edge` is the cell left of `row`, `None` at the grid edge.
+#[inline(always)]
+fn scan_from_left<T: Channels>(step: impl Fn(T, T) -> T, row: &mut [T], x0: usize, edge: Option<T>, f: &[T]) {
+    let (mut prev, cells) = match edge {
+        Some(prev) => (prev, &mut row[..]),
+        None => (row[0], &mut row[1..]),
+    };
+    let first = x0 + usize::from(edge.is_none()) - 1;
+    for (out, &f) in cells.iter_mut().zip(&f[first..]) {
+        *out = out.max(step(prev, f));
+        prev = *out;
+    }
+}
+
+/// Serial step right to left over `row` (columns `x0..`): light from the right
+/// neighbour. `edge` is the cell right of `row`, `None` at the grid edge.
+#[inline(always)]
+fn scan_from_right<T: Channels>(step: impl Fn(T, T) -> T, row: &mut [T], x0: usize, edge: Option<T>, f: &[T]) {
+    let n = row.len();
+    let (mut prev, cells) = match edge {
+        Some(prev) => (prev, &mut row[..]),
+        None => (row[n - 1], &mut row[..n - 1]),
+    };
+    let f = &f[x0 + 1..x0 + 1 + cells.len()];
+    for (out, &f) in cells.iter_mut().rev().zip(f.iter().rev()) {
+        *out = out.max(step(prev, f));
+        prev = *out;
+    }
+}
+
+/// Sweep one row segment (columns `x0..x0 + row.len()`) whose full-row inputs
+/// are `cur`.
+///
+/// `near` is the finished adjacent row and `far` the one beyond it (only read
+/// for knight moves); both must cover two columns either side of the segment.
+/// `edge` is the cell just before the segment in scan order.
+///
+/// `FORWARD` sweeps run top-down and left to right, otherwise bottom-up and
+/// right to left. `BOTH` pulls the diagonals and knight moves on both sides;
+/// without it only those on the side the sweep comes from.
+#[allow(clippy::too_many_arguments)]
+#[inline(always)]
+pub(super) fn sweep_segment<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool, const FORWARD: bool, const BOTH: bool>(
+    steps: &S,
+    row: &mut [T],
+    x0: usize,
+    mut edge: Option<T>,
+    cur: Factors<T>,
+    near: Option<Source<T>>,
+    far: Option<Source<T>>,
+) {
+    let straight = |light, f| steps.straight(light, f);
+    let diagonal = |light, f| steps.diagonal(light, f);
+    // Side the sweep comes from
+    let s: isize = if FORWARD { -1 } else { 1 };
+    let x1 = x0 + row.len();
+    let mut block = |b0: usize, b1: usize, edge: Option<T>| -> T {
+        let cells = &mut row[b0 - x0..b1 - x0];
+        if let Some(near) = near {
+            pull(straight, cells, b0, near, near.rows.straight, 0);
+            if DIAG {
+                pull(diagonal, cells, b0, near, near.rows.diagonal, s);
+                if BOTH {
+                    pull(diagonal, cells, b0, near, near.rows.diagonal, -s);
+                }
             }
-            if y > 0 {
-                max_prop = max_prop.max(propagate(att, decay, idx - w, 1.0));
-                if x > 0 {
-                    max_prop = max_prop.max(propagate(att, decay, idx - w - 1, diag));
+            if KNIGHT {
+                let (dn, dc) = (near.rows.decay, cur.decay);
+                pull_knight(steps, cells, b0, near, dn, dc, [2 * s, s, s]);
+                if BOTH {
+                    pull_knight(steps, cells, b0, near, dn, dc, [-2 * s, -s, -s]);
                 }
-                if x + 1 < w {
-                    max_prop = max_prop.max(propagate(att, decay, idx - w + 1, diag));
+                if let Some(far) = far {
+                    pull_knight(steps, cells, b0, far, dn, dn, [s, s, 0]);
+                    if BOTH {
+                        pull_knight(steps, cells, b0, far, dn, dn, [-s, -s, 0]);
+                    }
                 }
             }
-            att[idx] = max_prop;
+        }
+        if FORWARD {
+            scan_from_left(straight, cells, b0, edge, cur.straight);
+            cells[cells.len() - 1]
+        } else {
+            scan_from_right(straight, cells, b0, edge, cur.straight);
+            cells[0]
+        }
+    };
+    if FORWARD {
+        for (b0, b1) in column_blocks(x0, x1) {
+            edge = Some(block(b0, b1, edge));
+        }
+    } else {
+        for (b0, b1) in column_blocks(x0, x1).rev() {
+            edge = Some(block(b0, b1, edge));
         }
     }
 }
 
-/// Sweep bottom-up, checking right/down and both lower diagonals
+// ============================================================================
+// Four sweep patterns
+// ============================================================================
+//
+// Each sweep visits only the cells of `r`, but reads neighbours anywhere in the
+// `w x h` grid, so values outside the region act as fixed boundary conditions.
+//
+// `DIAG` enables the diagonal neighbours and `KNIGHT` the knight moves; they are
+// const generics so every kernel gets its own branch-free inner loop.
+
+/// Sweep the rows of `r` in the order given by `FORWARD` (see [`sweep_segment`])
 #[inline]
-fn sweep_up(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    for y in (0..h).rev() {
-        for x in (0..w).rev() {
-            let idx = y * w + x;
-            let mut max_prop = att[idx];
-            if x + 1 < w {
-                max_prop = max_prop.max(propagate(att, decay, idx + 1, 1.0));
-            }
-            if y + 1 < h {
-                max_prop = max_prop.max(propagate(att, decay, idx + w, 1.0));
-                if x > 0 {
-                    max_prop = max_prop.max(propagate(att, decay, idx + w - 1, diag));
-                }
-                if x + 1 < w {
-                    max_prop = max_prop.max(propagate(att, decay, idx + w + 1, diag));
-                }
-            }
-            att[idx] = max_prop;
-        }
+fn sweep_rows<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool, const FORWARD: bool, const BOTH: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    #[cfg(feature = "parallel")]
+    if front.sweep_rows::<T, S, DIAG, KNIGHT, FORWARD, BOTH>(steps, grid, att, w, h, r) {
+        return;
     }
+    #[cfg(not(feature = "parallel"))]
+    let _ = front;
+
+    // Row `i` of the split-off `done` rows, which is grid row `y`
+    fn source<'a, T: Channels>(done: &'a [T], i: usize, grid: Factors<'a, T>, y: usize, w: usize) -> Source<'a, T> {
+        Source { att: &done[i * w..(i + 1) * w], x: 0, rows: grid.row(y, w) }
+    }
+    for i in 0..r.y1 - r.y0 {
+        let y = if FORWARD { r.y0 + i } else { r.y1 - 1 - i };
+        // Split off the finished rows so they can be read while `y` is written
+        let (row, near, far) = if FORWARD {
+            let (done, rest) = att.split_at_mut(y * w);
+            let near = (y > 0).then(|| source(done, y - 1, grid, y - 1, w));
+            let far = (y > 1).then(|| source(done, y - 2, grid, y - 2, w));
+            (&mut rest[..w], near, far)
+        } else {
+            let (head, done) = att.split_at_mut((y + 1) * w);
+            let near = (y + 1 < h).then(|| source(done, 0, grid, y + 1, w));
+            let far = (y + 2 < h).then(|| source(done, 1, grid, y + 2, w));
+            (&mut head[y * w..], near, far)
+        };
+        let edge = if FORWARD { r.x0.checked_sub(1).map(|x| row[x]) } else { row.get(r.x1).copied() };
+        let cells = &mut row[r.x0..r.x1];
+        sweep_segment::<T, S, DIAG, KNIGHT, FORWARD, BOTH>(steps, cells, r.x0, edge, grid.row(y, w), near, far);
+    }
+}
+
+/// Sweep from top-left to bottom-right, checking left/up/up-left neighbors
+#[inline]
+fn sweep_tl_to_br<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_rows::<T, S, DIAG, KNIGHT, true, false>(steps, grid, att, w, h, r, front);
+}
+
+/// Sweep from bottom-right to top-left, checking right/down/down-right neighbors
+#[inline]
+fn sweep_br_to_tl<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_rows::<T, S, DIAG, KNIGHT, false, false>(steps, grid, att, w, h, r, front);
+}
+
+/// Sweep top-down, checking left/up and both upper diagonals
+#[inline]
+fn sweep_down<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_rows::<T, S, DIAG, KNIGHT, true, true>(steps, grid, att, w, h, r, front);
+}
+
+/// Sweep bottom-up, checking right/down and both lower diagonals
+#[inline]
+fn sweep_up<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_rows::<T, S, DIAG, KNIGHT, false, true>(steps, grid, att, w, h, r, front);
 }
 
 // ============================================================================
@@ -190,20 +1047,102 @@ fn sweep_up(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
 
 /// Forward sweeps: TL→BR, BR→TL, Down, Up
 #[inline]
-fn run_forward_sweeps(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    sweep_tl_to_br(decay, att, w, h, diag);
-    sweep_br_to_tl(decay, att, w, h, diag);
-    sweep_down(decay, att, w, h, diag);
-    sweep_up(decay, att, w, h, diag);
+fn forward_sweeps<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_tl_to_br::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_br_to_tl::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_down::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_up::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
 }
 
 /// Reverse sweeps: Up, Down, BR→TL, TL→BR (opposite order)
 #[inline]
-fn run_reverse_sweeps(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag: f32) {
-    sweep_up(decay, att, w, h, diag);
-    sweep_down(decay, att, w, h, diag);
-    sweep_br_to_tl(decay, att, w, h, diag);
-    sweep_tl_to_br(decay, att, w, h, diag);
+fn reverse_sweeps<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    sweep_up::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_down::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_br_to_tl::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+    sweep_tl_to_br::<T, S, DIAG, KNIGHT>(steps, grid, att, w, h, r, front);
+}
+
+/// Forward pass with the sweeps specialised for `kernel` and the step rule
+#[allow(clippy::too_many_arguments)]
+pub(super) fn forward_pass<T: Channels, S: Steps<T>>(
+    steps: &S,
+    kernel: Kernel,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    match kernel {
+        Kernel::Four => forward_sweeps::<T, S, false, false>(steps, grid, att, w, h, r, front),
+        Kernel::Eight => forward_sweeps::<T, S, true, false>(steps, grid, att, w, h, r, front),
+        Kernel::Sixteen => forward_sweeps::<T, S, true, true>(steps, grid, att, w, h, r, front),
+    }
+}
+
+/// Reverse pass with the sweeps specialised for `kernel` and the step rule
+#[allow(clippy::too_many_arguments)]
+pub(super) fn reverse_pass<T: Channels, S: Steps<T>>(
+    steps: &S,
+    kernel: Kernel,
+    grid: Factors<T>,
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    match kernel {
+        Kernel::Four => reverse_sweeps::<T, S, false, false>(steps, grid, att, w, h, r, front),
+        Kernel::Eight => reverse_sweeps::<T, S, true, false>(steps, grid, att, w, h, r, front),
+        Kernel::Sixteen => reverse_sweeps::<T, S, true, true>(steps, grid, att, w, h, r, front),
+    }
+}
+
+/// Forward pass with the sweeps specialised for the kernel and decay model of `s`
+pub(crate) fn run_forward_sweeps<M: DecayModel, T: Channels>(
+    s: &Sweeping<M>,
+    decay: &[T],
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    let steps = DecaySteps { model: &s.model, diag: s.diagonal_decay_mult };
+    forward_pass(&steps, s.kernel, Factors::decay_only(decay), att, w, h, r, front);
+}
+
+/// Reverse pass with the sweeps specialised for the kernel and decay model of `s`
+pub(crate) fn run_reverse_sweeps<M: DecayModel, T: Channels>(
+    s: &Sweeping<M>,
+    decay: &[T],
+    att: &mut [T],
+    w: usize,
+    h: usize,
+    r: Region,
+    front: &mut Wavefront,
+) {
+    let steps = DecaySteps { model: &s.model, diag: s.diagonal_decay_mult };
+    reverse_pass(&steps, s.kernel, Factors::decay_only(decay), att, w, h, r, front);
 }
 
 // ============================================================================
@@ -211,7 +1150,7 @@ fn run_reverse_sweeps(decay: &[f32], att: &mut [f32], w: usize, h: usize, diag:
 // ============================================================================
 
 /// Convert Vec<Vec<f32>> to flat Vec<f32> (row-major: y * width + x)
-pub fn flatten_grid(grid: &Vec<Vec<f32>>) -> Vec<f32> {
+pub fn flatten_grid(grid: &[Vec<f32>]) -> Vec<f32> {
     let width = grid.len();
     let height = grid[0].len();
     let mut flat = vec![0.0f32; width * height];
@@ -0,0 +1,138 @@
+//! Precomputed per-cell step factors for static decay grids.
+//!
+//! Every sweep step evaluates [`DecayModel::factor`] (for [`Linear`],
+//! `1 - decay * mult`) for the cell the light leaves, in all eight sweeps and on
+//! every call. A [`TransmittanceGrid`] computes the straight and diagonal factors
+//! of each cell once, so sweeps over it only [`apply`](DecayModel::apply) them.
+//! Knight moves are charged by the cells they pass between, so
+//! [`Kernel::Sixteen`](super::Kernel::Sixteen) still derives those from the
+//! stored decay.
+
+use super::model::{DecayModel, Linear};
+use super::sweeping::{Boundary, FactorSteps, Factors, Sweeping, forward_pass, join_passes, merge_max, reverse_pass};
+
+/// Straight and diagonal step factors of a decay grid, for one decay model and
+/// diagonal multiplier.
+///
+/// Edit cells through [`set_decay`](Self::set_decay) so the factors never go
+/// stale.
+#[derive(Debug, Clone)]
+pub struct TransmittanceGrid<M: DecayModel = Linear> {
+    model: M,
+    diagonal_decay_mult: f32,
+    width: usize,
+    height: usize,
+    decay: Vec<f32>,
+    straight: Vec<f32>,
+    diagonal: Vec<f32>,
+    /// Smallest decay in the grid, for the sweep window
+    min_decay: f32,
+}
+
+impl<M: DecayModel> TransmittanceGrid<M> {
+    /// Compute the factors of `decay_flat` for the model and diagonal multiplier
+    /// of `sweeping`.
+    pub fn new(sweeping: &Sweeping<M>, decay_flat: &[f32], width: usize, height: usize) -> Self {
+        assert_eq!(decay_flat.len(), width * height, "decay grid does not match {width}x{height}");
+        let mut grid = TransmittanceGrid {
+            model: sweeping.model.clone(),
+            diagonal_decay_mult: sweeping.diagonal_decay_mult,
+            width,
+            height,
+            decay: Vec::new(),
+            straight: Vec::new(),
+            diagonal: Vec::new(),
+            min_decay: f32::INFINITY,
+        };
+        grid.set_decay_grid(decay_flat);
+        grid
+    }
+
+    /// Grid width in cells.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Grid height in cells.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The decay grid the factors were computed from.
+    pub fn decay(&self) -> &[f32] {
+        &self.decay
+    }
+
+    /// Whether the factors fit `sweeping` (same diagonal multiplier).
+    pub fn matches(&self, sweeping: &Sweeping<M>) -> bool {
+        self.diagonal_decay_mult.to_bits() == sweeping.diagonal_decay_mult.to_bits()
+    }
+
+    /// Change the decay of cell `idx` (row-major) and recompute its factors.
+    pub fn set_decay(&mut self, idx: usize, decay: f32) {
+        let old = std::mem::replace(&mut self.decay[idx], decay);
+        self.straight[idx] = self.model.factor(decay, 1.0);
+        self.diagonal[idx] = self.model.factor(decay, self.diagonal_decay_mult);
+        if decay < self.min_decay {
+            self.min_decay = decay;
+        } else if old == self.min_decay && decay > old {
+            // The minimum may have been this cell
+            self.min_decay = self.decay.iter().copied().fold(f32::INFINITY, f32::min);
+        }
+    }
+
+    /// Replace the whole decay grid (same size) and recompute every factor.
+    pub fn set_decay_grid(&mut self, decay_flat: &[f32]) {
+        assert_eq!(decay_flat.len(), self.width * self.height, "decay grid size changed");
+        let (model, diag) = (&self.model, self.diagonal_decay_mult);
+        self.decay = decay_flat.to_vec();
+        self.straight = decay_flat.iter().map(|&d| model.factor(d, 1.0)).collect();
+        self.diagonal = decay_flat.iter().map(|&d| model.factor(d, diag)).collect();
+        self.min_decay = decay_flat.iter().copied().fold(f32::INFINITY, f32::min);
+    }
+
+    /// The grids as row kernel inputs
+    fn factors(&self) -> Factors<'_> {
+        Factors { straight: &self.straight, diagonal: &self.diagonal, decay: &self.decay }
+    }
+}
+
+impl<M: DecayModel> Sweeping<M> {
+    /// [`calculate_many`](Self::calculate_many) over a precomputed
+    /// [`TransmittanceGrid`], with bit-identical results. A single light is
+    /// `&[(x, y, intensity)]`.
+    ///
+    /// With a wrapping [`boundary`](Self::boundary) this falls back to sweeping
+    /// the stored decay grid.
+    ///
+    /// Panics if the grid was built for a different diagonal multiplier (see
+    /// [`TransmittanceGrid::matches`]). Otherwise unchecked like
+    /// [`calculate_flat`](Self::calculate_flat).
+    pub fn calculate_with(&self, grid: &TransmittanceGrid<M>, lights: &[(usize, usize, f32)]) -> Vec<f32> {
+        assert!(grid.matches(self), "TransmittanceGrid was built for a different diagonal_decay_mult");
+        let (width, height) = (grid.width, grid.height);
+        if self.boundary != Boundary::Clamp {
+            return self.calculate_many(&grid.decay, width, height, lights);
+        }
+
+        let mut forward = vec![0.0f32; width * height];
+        for &(x, y, intensity) in lights {
+            let idx = y * width + x;
+            forward[idx] = forward[idx].max(intensity);
+        }
+        let window = self.window_for(grid.min_decay, width, height, lights);
+        if window.is_empty() {
+            return forward;
+        }
+
+        let steps = FactorSteps(&self.model);
+        let mut reverse = forward.clone();
+        join_passes(
+            &mut Default::default(),
+            |front| forward_pass(&steps, self.kernel, grid.factors(), &mut forward, width, height, window, front),
+            |front| reverse_pass(&steps, self.kernel, grid.factors(), &mut reverse, width, height, window, front),
+        );
+        merge_max(&mut forward, &reverse, width, window);
+        forward
+    }
+}
@@ -0,0 +1,196 @@
+//! Parallelism inside a single sweep.
+//!
+//! A sweep row depends on the finished row before it (up to two columns either
+//! side) and, through the scan, on the cells before it in the same row. With the
+//! columns split into strips, strip `s` can sweep its `k`-th row once
+//!
+//! * the strip the scan comes from has finished row `k`, and
+//! * the strip on the other side has finished row `k - 1`,
+//!
+//! so the strips advance as a staggered wavefront, one row apart. Strips are not
+//! tied to threads: every worker keeps claiming whichever strip is ready, so a
+//! sweep completes however many pool threads join in, and each cell sees exactly
+//! the inputs of the serial sweep, giving a bit-identical result.
+//!
+//! The forward and reverse passes run side by side, so each pass only gets its
+//! share of the pool (see [`join_passes`](super::sweeping::join_passes)). Strip
+//! state lives in a [`Wavefront`] that is reused between sweeps, and workers are
+//! forked with `rayon::join`, whose jobs live on the stack, so a sweep with a
+//! warm [`Wavefront`] does not allocate.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use super::region::Region;
+use super::sweeping::{Channels, Factors, Source, Steps, sweep_segment};
+
+/// Narrowest strip handed to a worker, so each claimed row pays for its
+/// synchronisation; also keeps strips wider than the two columns a knight move
+/// reaches into its neighbours
+const MIN_STRIP_WIDTH: usize = 256;
+
+/// Smallest region (in cells) that is swept as a wavefront
+const MIN_WAVEFRONT_CELLS: usize = 1 << 20;
+
+/// Progress of one column strip
+#[derive(Debug)]
+struct Strip {
+    x0: usize,
+    x1: usize,
+    /// Rows finished, counted in sweep order
+    rows_done: AtomicUsize,
+    /// Set while a worker is sweeping a row of this strip
+    busy: AtomicBool,
+}
+
+/// The attenuation grid, shared by the workers of one sweep.
+///
+/// A worker only writes the row segment of the strip it claimed, and only reads
+/// cells the strip counters mark as final, so accesses never overlap a write.
+#[derive(Clone, Copy)]
+struct Grid<T>(*mut T);
+
+// SAFETY: access is coordinated by the strip counters as described above
+unsafe impl<T: Send> Send for Grid<T> {}
+unsafe impl<T: Send> Sync for Grid<T> {}
+
+/// Strip state and worker budget of one pass, kept between sweeps.
+#[derive(Debug, Default)]
+pub(crate) struct Wavefront {
+    strips: Vec<Strip>,
+    /// Pool threads this pass may use (0 for the whole pool)
+    pub(super) workers: usize,
+}
+
+// The strips are scratch: a clone starts with an empty buffer
+impl Clone for Wavefront {
+    fn clone(&self) -> Self {
+        Wavefront { strips: Vec::new(), workers: self.workers }
+    }
+}
+
+impl Wavefront {
+    /// Sweep `r` as a wavefront of column strips on the rayon pool.
+    ///
+    /// Returns `false` without touching `att` when the region is too small or
+    /// the pass has a single worker; the caller then sweeps serially.
+    pub(super) fn sweep_rows<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool, const FORWARD: bool, const BOTH: bool>(
+        &mut self,
+        steps: &S,
+        grid: Factors<T>,
+        att: &mut [T],
+        w: usize,
+        h: usize,
+        r: Region,
+    ) -> bool {
+        let workers = if self.workers == 0 { rayon::current_num_threads() } else { self.workers };
+        let count = workers.min(r.width() / MIN_STRIP_WIDTH);
+        if count < 2 || r.area() < MIN_WAVEFRONT_CELLS {
+            return false;
+        }
+        self.strips.clear();
+        self.strips.extend((0..count).map(|i| Strip {
+            x0: r.x0 + r.width() * i / count,
+            x1: r.x0 + r.width() * (i + 1) / count,
+            rows_done: AtomicUsize::new(0),
+            busy: AtomicBool::new(false),
+        }));
+        let strips = &self.strips[..];
+        let rows = r.height();
+        let finished = AtomicUsize::new(0);
+        let cells = Grid(att.as_mut_ptr());
+
+        // Sweep the next row of strip `s` if it is free and ready
+        let step = |s: usize| -> bool {
+            let strip = &strips[s];
+            if strip.busy.swap(true, Ordering::Acquire) {
+                return false;
+            }
+            let k = strip.rows_done.load(Ordering::Relaxed);
+            let (before, after) = (s.checked_sub(1).map(|i| &strips[i]), strips.get(s + 1));
+            let (from, other) = if FORWARD { (before, after) } else { (after, before) };
+            let ready = k < rows
+                && from.is_none_or(|n| n.rows_done.load(Ordering::Acquire) > k)
+                && other.is_none_or(|n| n.rows_done.load(Ordering::Acquire) >= k);
+            if ready {
+                let y = if FORWARD { r.y0 + k } else { r.y1 - 1 - k };
+                // SAFETY: this worker owns the strip's row `y`; the neighbouring
+                // strips have finished the rows and edge cell read below
+                unsafe { sweep_strip_row::<T, S, DIAG, KNIGHT, FORWARD, BOTH>(steps, grid, cells, w, h, y, strip) };
+                strip.rows_done.store(k + 1, Ordering::Release);
+                if k + 1 == rows {
+                    finished.fetch_add(1, Ordering::Release);
+                }
+            }
+            strip.busy.store(false, Ordering::Release);
+            ready
+        };
+
+        // A worker that finds no strip ready after trying each one yields its
+        // thread instead of spinning, so waiting workers never starve the ones
+        // doing the sweep when the pool has more threads than free cores
+        let worker = |first: usize| {
+            let (mut s, mut misses) = (first, 0);
+            while finished.load(Ordering::Acquire) < count {
+                if step(s) {
+                    misses = 0;
+                    continue;
+                }
+                s = (s + 1) % count;
+                misses += 1;
+                if misses >= count {
+                    std::thread::yield_now();
+                    misses = 0;
+                }
+            }
+        };
+        fan_out(0, count, &worker);
+        true
+    }
+}
+
+/// Run `worker(first..first + n)` as `n` rayon jobs forked with `join`.
+///
+/// Any single worker finishes the whole sweep on its own, so workers that are
+/// never stolen just find it done.
+fn fan_out(first: usize, n: usize, worker: &(impl Fn(usize) + Sync)) {
+    if n == 1 {
+        worker(first);
+    } else {
+        let half = n / 2;
+        rayon::join(|| fan_out(first, half, worker), || fan_out(first + half, n - half, worker));
+    }
+}
+
+/// Sweep row `y` of one strip.
+///
+/// # Safety
+///
+/// No other thread may access the strip's cells of row `y`, or write the edge
+/// cell before it or the two rows before it (within two columns of the strip).
+#[inline(always)]
+unsafe fn sweep_strip_row<T: Channels, S: Steps<T>, const DIAG: bool, const KNIGHT: bool, const FORWARD: bool, const BOTH: bool>(
+    steps: &S,
+    grid: Factors<T>,
+    cells: Grid<T>,
+    w: usize,
+    h: usize,
+    y: usize,
+    strip: &Strip,
+) {
+    let (x0, x1) = (strip.x0, strip.x1);
+    let (c0, c1) = (x0.saturating_sub(2), (x1 + 2).min(w));
+    let source = |ny: usize| Source {
+        att: unsafe { std::slice::from_raw_parts(cells.0.add(ny * w + c0), c1 - c0) },
+        x: c0,
+        rows: grid.row(ny, w),
+    };
+    let (near, far, edge) = if FORWARD {
+        let edge = x0.checked_sub(1).map(|x| unsafe { *cells.0.add(y * w + x) });
+        ((y > 0).then(|| source(y - 1)), (y > 1).then(|| source(y - 2)), edge)
+    } else {
+        let edge = (x1 < w).then(|| unsafe { *cells.0.add(y * w + x1) });
+        ((y + 1 < h).then(|| source(y + 1)), (y + 2 < h).then(|| source(y + 2)), edge)
+    };
+    let row = unsafe { std::slice::from_raw_parts_mut(cells.0.add(y * w + x0), x1 - x0) };
+    sweep_segment::<T, S, DIAG, KNIGHT, FORWARD, BOTH>(steps, row, x0, edge, grid.row(y, w), near, far);
+}
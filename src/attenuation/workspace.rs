@@ -0,0 +1,89 @@
+//! Reusable scratch buffers for allocation-free relighting.
+//!
+//! [`Sweeping::calculate_many`] allocates a seed grid, a reverse-pass copy and
+//! its result on every call. A [`SweepWorkspace`] keeps the reverse-pass buffer
+//! and the wavefront strip state between calls and writes into a caller-owned
+//! output, so once it has seen the largest grid it will be used with, relighting
+//! does no heap allocation.
+
+use super::model::{DecayModel, Linear};
+use super::region::Region;
+use super::sweeping::{Boundary, Sweeping, Wavefront, join_passes, merge_max, run_forward_sweeps, run_reverse_sweeps};
+
+/// A [`Sweeping`] plus the scratch memory its passes need.
+#[derive(Debug, Clone)]
+pub struct SweepWorkspace<M: DecayModel = Linear> {
+    /// Settings used for every calculation
+    pub sweeping: Sweeping<M>,
+    reverse: Vec<f32>,
+    /// Strip state of the forward and reverse passes' wavefronts
+    fronts: [Wavefront; 2],
+}
+
+impl<M: DecayModel> SweepWorkspace<M> {
+    /// Create an empty workspace; buffers grow on first use.
+    pub fn new(sweeping: Sweeping<M>) -> Self {
+        SweepWorkspace { sweeping, reverse: Vec::new(), fronts: Default::default() }
+    }
+
+    /// Create a workspace with buffers already sized for `width x height` grids.
+    pub fn with_capacity(sweeping: Sweeping<M>, width: usize, height: usize) -> Self {
+        SweepWorkspace { sweeping, reverse: vec![0.0; width * height], fronts: Default::default() }
+    }
+
+    /// Allocation-free [`calculate_many`](Sweeping::calculate_many): writes the
+    /// combined attenuation of `(x, y, intensity)` lights into `out`
+    /// (`out.len() == width * height`). A single light is `&[(x, y, intensity)]`.
+    ///
+    /// Only allocates when the grid is larger (or, with the `parallel` feature,
+    /// split into more wavefront strips) than any seen before, or with a
+    /// wrapping [`Boundary`] (which sweeps a padded copy of the grid).
+    pub fn calculate_into(
+        &mut self,
+        decay_flat: &[f32],
+        width: usize,
+        height: usize,
+        lights: &[(usize, usize, f32)],
+        out: &mut [f32],
+    ) {
+        let s = &self.sweeping;
+        if s.boundary != Boundary::Clamp {
+            return s.calculate_many_into(decay_flat, width, height, lights, out);
+        }
+
+        out.fill(0.0);
+        for &(x, y, intensity) in lights {
+            let idx = y * width + x;
+            out[idx] = out[idx].max(intensity);
+        }
+        let window = s.light_window(decay_flat, width, height, lights);
+        if window.is_empty() {
+            return;
+        }
+
+        // The passes read up to two cells outside the window (knight moves), so
+        // the reverse buffer only needs the seeds in that band
+        let band = Region {
+            x0: window.x0.saturating_sub(2),
+            y0: window.y0.saturating_sub(2),
+            x1: (window.x1 + 2).min(width),
+            y1: (window.y1 + 2).min(height),
+        };
+        if self.reverse.len() < width * height {
+            self.reverse.resize(width * height, 0.0);
+        }
+        let reverse = &mut self.reverse[..width * height];
+        for y in band.y0..band.y1 {
+            let row = y * width;
+            reverse[row + band.x0..row + band.x1].copy_from_slice(&out[row + band.x0..row + band.x1]);
+        }
+
+        join_passes(
+            &mut self.fronts,
+            |front| run_forward_sweeps(s, decay_flat, out, width, height, window, front),
+            |front| run_reverse_sweeps(s, decay_flat, reverse, width, height, window, front),
+        );
+
+        merge_max(out, reverse, width, window);
+    }
+}
@@ -3,6 +3,8 @@
 //! This module handles applying colors to attenuation grids and blending
 //! multiple colored light contributions together.
 
+use crate::attenuation::{Cone, DecayModel, Shape, SkyLight, Sweeping};
+
 /// RGBA color with floating point components
 #[derive(Debug, Clone, Copy)]
 pub struct RGBA {
@@ -22,15 +24,80 @@ impl RGBA {
     }
 }
 
-/// A colored point light with position, color, and intensity
+/// A colored light with position, color, and intensity
+///
+/// Outside this crate, build it with [`point`](Self::point) (or [`spot`](Self::spot),
+/// [`area`](Self::area)) and the `with_*` methods, so new fields are not breaking.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ColoredLight {
     pub color: (f32, f32, f32), // RGB 0.0-1.0
     pub intensity: f32,
-    pub position: (usize, usize),
+    pub position: (usize, usize), // anchor cell of `shape`
+    pub cone: Option<Cone>, // None = omnidirectional, Some = spot light
+    pub shape: Shape, // Point, or a line/area anchored at `position`
+}
+
+impl ColoredLight {
+    /// Omnidirectional point light.
+    pub fn point(color: (f32, f32, f32), intensity: f32, position: (usize, usize)) -> Self {
+        ColoredLight { color, intensity, position, cone: None, shape: Shape::Point }
+    }
+
+    /// Spot light shining into `cone`.
+    pub fn spot(color: (f32, f32, f32), intensity: f32, position: (usize, usize), cone: Cone) -> Self {
+        Self::point(color, intensity, position).with_cone(cone)
+    }
+
+    /// Line or area light anchored at `position` (see [`Shape`]).
+    pub fn area(color: (f32, f32, f32), intensity: f32, position: (usize, usize), shape: Shape) -> Self {
+        Self::point(color, intensity, position).with_shape(shape)
+    }
+
+    /// Restrict the light to `cone`, making it a spot light.
+    pub fn with_cone(mut self, cone: Cone) -> Self {
+        self.cone = Some(cone);
+        self
+    }
+
+    /// Emit from `shape` anchored at `position` instead of a single cell.
+    pub fn with_shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Attenuation for this light's position and shape (row-major: y * width + x).
+    ///
+    /// Spot lights only propagate inside their cone (see
+    /// [`calculate_spot`](Sweeping::calculate_spot)); the cone's soft edge is
+    /// applied by [`apply_light_color`].
+    pub fn attenuation<M: DecayModel>(&self, sweeping: &Sweeping<M>, decay_flat: &[f32], width: usize, height: usize) -> Vec<f32> {
+        let (x, y) = self.position;
+        match &self.cone {
+            Some(cone) => {
+                let decay = cone.occlude(decay_flat, width, x, y, f32::INFINITY);
+                sweeping.calculate_shape(&decay, width, height, x, y, &self.shape, 1.0)
+            }
+            None => sweeping.calculate_shape(decay_flat, width, height, x, y, &self.shape, 1.0),
+        }
+    }
+
+    /// Cone factor for cell `(x, y)` (1.0 for omnidirectional lights)
+    fn cone_factor(&self, x: usize, y: usize) -> f32 {
+        match &self.cone {
+            Some(cone) => cone.factor(
+                x as f32 - self.position.0 as f32,
+                y as f32 - self.position.1 as f32,
+            ),
+            None => 1.0,
+        }
+    }
 }
 
 /// Applies light color and intensity to an attenuation grid.
+///
+/// For spot lights the cone factor is applied here; `attenuation` should come
+/// from [`ColoredLight::attenuation`] so no light leaks around walls into the cone.
 /// 
 /// # Arguments
 /// * `attenuation` - 2D grid of attenuation values (0.0 to 1.0)
@@ -39,7 +106,7 @@ pub struct ColoredLight {
 /// # Returns
 /// RGBA contribution grid for each cell
 pub fn apply_light_color(
-    attenuation: &Vec<Vec<f32>>,
+    attenuation: &[Vec<f32>],
     light: &ColoredLight,
 ) -> Vec<Vec<RGBA>> {
     let width = attenuation.len();
@@ -49,7 +116,7 @@ pub fn apply_light_color(
 
     for x in 0..width {
         for y in 0..height {
-            let att = attenuation[x][y];
+            let att = attenuation[x][y] * light.cone_factor(x, y);
             result[x][y] = RGBA::new(
                 light.color.0 * light.intensity * att,
                 light.color.1 * light.intensity * att,
@@ -62,6 +129,84 @@ pub fn apply_light_color(
     result
 }
 
+/// Lights a scene with tinted materials, propagating each color channel separately.
+///
+/// Unlike [`apply_light_color`], which multiplies a scalar attenuation grid by the
+/// light color, this lets each cell absorb channels differently (see
+/// [`Sweeping::calculate_rgb`]).
+///
+/// # Arguments
+/// * `sweeping` - Sweep settings and decay model to propagate with
+/// * `decay_rgb` - Flat per-cell `[r, g, b]` decay (row-major: y * width + x)
+/// * `width`, `height` - Grid dimensions
+/// * `light` - The colored light to apply
+///
+/// # Returns
+/// RGBA contribution grid for each cell, indexed `[x][y]` like [`apply_light_color`]
+pub fn apply_light_color_rgb<M: DecayModel>(
+    sweeping: &Sweeping<M>,
+    decay_rgb: &[[f32; 3]],
+    width: usize,
+    height: usize,
+    light: &ColoredLight,
+) -> Vec<Vec<RGBA>> {
+    let (x, y) = light.position;
+    let source = [
+        light.color.0 * light.intensity,
+        light.color.1 * light.intensity,
+        light.color.2 * light.intensity,
+    ];
+    let lights: Vec<_> = light
+        .shape
+        .cells(x, y, width, height)
+        .into_iter()
+        .map(|(cx, cy)| (cx, cy, source))
+        .collect();
+    let light_map = match &light.cone {
+        Some(cone) => {
+            let decay = cone.occlude(decay_rgb, width, x, y, [f32::INFINITY; 3]);
+            sweeping.calculate_rgb_many(&decay, width, height, &lights)
+        }
+        None => sweeping.calculate_rgb_many(decay_rgb, width, height, &lights),
+    };
+
+    let mut result = vec![vec![RGBA::black(); height]; width];
+    for (i, rgb) in light_map.iter().enumerate() {
+        let (cx, cy) = (i % width, i / width);
+        let f = light.cone_factor(cx, cy);
+        result[cx][cy] = RGBA::new(rgb[0] * f, rgb[1] * f, rgb[2] * f, 1.0);
+    }
+
+    result
+}
+
+/// Lights a scene with ambient sky light in the sky's colour.
+///
+/// # Arguments
+/// * `sweeping` - Sweep settings and decay model to propagate with
+/// * `decay_flat` - Flat per-cell decay (row-major: y * width + x)
+/// * `width`, `height` - Grid dimensions
+/// * `sky` - Sky intensity, colour, day-time factor and open cells
+///
+/// # Returns
+/// RGBA contribution grid for each cell, indexed `[x][y]` like [`apply_light_color`]
+pub fn apply_sky_light<M: DecayModel>(
+    sweeping: &Sweeping<M>,
+    decay_flat: &[f32],
+    width: usize,
+    height: usize,
+    sky: &SkyLight,
+) -> Vec<Vec<RGBA>> {
+    let light_map = sweeping.calculate_sky(decay_flat, width, height, sky);
+
+    let mut result = vec![vec![RGBA::black(); height]; width];
+    for (i, att) in light_map.iter().enumerate() {
+        result[i % width][i / width] = RGBA::new(sky.color.0 * att, sky.color.1 * att, sky.color.2 * att, 1.0);
+    }
+
+    result
+}
+
 /// Blends multiple light contributions using additive blending.
 /// 
 /// # Arguments
@@ -93,14 +238,14 @@ pub fn blend_lights(contributions: &[Vec<Vec<RGBA>>]) -> Vec<Vec<RGBA>> {
 }
 
 /// Converts an RGBA grid to a formatted string for debugging
-pub fn rgba_grid_to_string(grid: &Vec<Vec<RGBA>>) -> String {
+pub fn rgba_grid_to_string(grid: &[Vec<RGBA>]) -> String {
     let width = grid.len();
     let height = if width > 0 { grid[0].len() } else { 0 };
     let mut result = String::new();
 
     for y in 0..height {
-        for x in 0..width {
-            let c = &grid[x][y];
+        for column in grid {
+            let c = &column[y];
             result.push_str(&format!("({:.1},{:.1},{:.1}) ", c.r, c.g, c.b));
         }
         result.push('\n');
@@ -132,6 +277,8 @@ mod tests {
             color: (1.0, 0.5, 0.0),
             intensity: 10.0,
             position: (0, 0),
+            cone: None,
+            shape: Shape::Point,
         };
 
         let result = apply_light_color(&attenuation, &light);
@@ -155,11 +302,53 @@ mod tests {
         assert_eq!((result[0][0].r, result[0][0].g, result[0][0].b), (5.0, 0.0, 5.0));
     }
 
+    #[test]
+    fn test_apply_light_color_rgb_tinted_glass() {
+        // 7x1 corridor: white light at x=0, red glass at x=3
+        let mut decay_rgb = vec![[0.1f32; 3]; 7];
+        decay_rgb[3] = [0.05, 0.9, 0.9];
+        let light = ColoredLight::point((1.0, 1.0, 1.0), 2.0, (0, 0));
+
+        let result = apply_light_color_rgb(&Sweeping::new(), &decay_rgb, 7, 1, &light);
+
+        // Before the glass the light is still white
+        assert_eq!(result[2][0].r, result[2][0].g);
+        // Behind the glass red dominates
+        let behind = &result[5][0];
+        assert!(behind.r > 5.0 * behind.g, "expected red tint, got {:?}", behind);
+        assert_eq!(behind.g, behind.b);
+    }
+
+    #[test]
+    fn test_apply_light_color_spot() {
+        // Spot pointing right from the middle of a 3x3 grid
+        let attenuation = vec![vec![1.0_f32; 3]; 3];
+        let cone = Cone::new(0.0, std::f32::consts::FRAC_PI_4, 0.0);
+        let light = ColoredLight::spot((1.0, 1.0, 1.0), 1.0, (1, 1), cone);
+
+        let result = apply_light_color(&attenuation, &light);
+
+        assert_eq!(result[1][1].r, 1.0); // source
+        assert_eq!(result[2][1].r, 1.0); // on axis
+        assert_eq!(result[0][1].r, 0.0); // behind
+        assert_eq!(result[1][0].r, 0.0); // 90° off axis
+    }
+
+    #[test]
+    fn test_builder_methods() {
+        let cone = Cone::new(0.0, 0.5, 0.0);
+        let shape = Shape::Line { to: (4, 0) };
+        let light = ColoredLight::point((1.0, 1.0, 1.0), 2.0, (1, 1)).with_cone(cone).with_shape(shape.clone());
+        assert_eq!(light.cone, Some(cone));
+        assert_eq!(light.shape, shape);
+        assert_eq!(ColoredLight::spot((1.0, 1.0, 1.0), 2.0, (1, 1), cone).cone, Some(cone));
+    }
+
     #[test]
     fn test_empty_inputs() {
         // Empty grids should return empty results
         let empty_att: Vec<Vec<f32>> = vec![];
-        let light = ColoredLight { color: (1.0, 1.0, 1.0), intensity: 10.0, position: (0, 0) };
+        let light = ColoredLight { color: (1.0, 1.0, 1.0), intensity: 10.0, position: (0, 0), cone: None, shape: Shape::Point };
         assert!(apply_light_color(&empty_att, &light).is_empty());
         assert!(blend_lights(&[]).is_empty());
     }
@@ -249,8 +249,6 @@ impl BlurPipeline {
         let device = &ctx.device;
         
         let (width, height) = self.full_size;
-        let half_width = width / 2;
-        let half_height = height / 2;
         
         // Upload uniforms for downsample (full -> half)
         let downsample_uniforms = BlurUniforms {
@@ -352,8 +350,8 @@ impl BlurPipeline {
             pass.set_pipeline(&self.downsample_pipeline);
             pass.set_bind_group(0, downsample_bg, &[]);
             pass.dispatch_workgroups(
-                (half_width + 7) / 8,
-                (half_height + 7) / 8,
+                half_width.div_ceil(8),
+                half_height.div_ceil(8),
                 1,
             );
         }
@@ -367,8 +365,8 @@ impl BlurPipeline {
             pass.set_pipeline(&self.upsample_pipeline);
             pass.set_bind_group(0, upsample_bg, &[]);
             pass.dispatch_workgroups(
-                (width + 7) / 8,
-                (height + 7) / 8,
+                width.div_ceil(8),
+                height.div_ceil(8),
                 1,
             );
         }
@@ -480,7 +478,7 @@ impl WallOverlayPipeline {
         });
         
         // Wall buffer
-        let wall_buffer_size = ((num_cells + 31) / 32 * std::mem::size_of::<u32>()) as u64;
+        let wall_buffer_size = (num_cells.div_ceil(32) * std::mem::size_of::<u32>()) as u64;
         let wall_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Wall Overlay Wall Buffer"),
             size: wall_buffer_size,
@@ -534,7 +532,7 @@ impl WallOverlayPipeline {
     
     /// Upload wall data
     pub fn upload_walls(&self, ctx: &GpuContext, walls: &[bool]) {
-        let num_words = (walls.len() + 31) / 32;
+        let num_words = walls.len().div_ceil(32);
         let mut packed = vec![0u32; num_words];
         
         for (i, &is_wall) in walls.iter().enumerate() {
@@ -567,8 +565,8 @@ impl WallOverlayPipeline {
             pass.set_pipeline(&self.pipeline);
             pass.set_bind_group(0, bind_group, &[]);
             pass.dispatch_workgroups(
-                (width + 7) / 8,
-                (height + 7) / 8,
+                width.div_ceil(8),
+                height.div_ceil(8),
                 1,
             );
         }
@@ -150,7 +150,7 @@ impl BlendToTexturePipeline {
         
         // Create buffers
         let grid_buffer_size = (num_cells * std::mem::size_of::<f32>()) as u64;
-        let wall_buffer_size = ((num_cells + 31) / 32 * std::mem::size_of::<u32>()) as u64;
+        let wall_buffer_size = (num_cells.div_ceil(32) * std::mem::size_of::<u32>()) as u64;
         
         let create_grid_buffer = |label: &str| {
             device.create_buffer(&wgpu::BufferDescriptor {
@@ -284,7 +284,7 @@ impl BlendToTexturePipeline {
     
     /// Upload wall data (packed as bits)
     pub fn upload_walls(&self, ctx: &GpuContext, walls: &[bool]) {
-        let num_words = (walls.len() + 31) / 32;
+        let num_words = walls.len().div_ceil(32);
         let mut packed = vec![0u32; num_words];
         
         for (i, &is_wall) in walls.iter().enumerate() {
@@ -316,8 +316,8 @@ impl BlendToTexturePipeline {
             compute_pass.set_pipeline(&self.blend_pipeline);
             compute_pass.set_bind_group(0, &self.bind_group, &[]);
             
-            let workgroups_x = (self.grid_size.0 + 7) / 8;
-            let workgroups_y = (self.grid_size.1 + 7) / 8;
+            let workgroups_x = self.grid_size.0.div_ceil(8);
+            let workgroups_y = self.grid_size.1.div_ceil(8);
             compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
         }
         
@@ -339,8 +339,8 @@ impl BlendToTexturePipeline {
             compute_pass.set_pipeline(&self.single_pipeline);
             compute_pass.set_bind_group(0, &self.bind_group, &[]);
             
-            let workgroups_x = (self.grid_size.0 + 7) / 8;
-            let workgroups_y = (self.grid_size.1 + 7) / 8;
+            let workgroups_x = self.grid_size.0.div_ceil(8);
+            let workgroups_y = self.grid_size.1.div_ceil(8);
             compute_pass.dispatch_workgroups(workgroups_x, workgroups_y, 1);
         }
         
@@ -8,11 +8,12 @@ use winit::{
     keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
-use rayon::prelude::*;
 
-use crate::attenuation::Sweeping;
+use lighting_test::attenuation::{AttenuationAlgorithm, BakedLighting, Combine, Sweeping};
 use crate::gpu::{GpuContext, DisplayPipeline, BlendPipeline, BlendToTexturePipeline, BlendUniforms, BlurPipeline, WallOverlayPipeline};
-use crate::render::NormalizationMode;
+use lighting_test::render::NormalizationMode;
+
+use super::SWEEPING;
 
 /// Configuration for the GPU viewer
 #[derive(Clone)]
@@ -49,6 +50,7 @@ struct ViewerState {
     config: GpuViewerConfig,
     gpu_ctx: GpuContext,
     display_pipeline: DisplayPipeline,
+    #[allow(dead_code)] // Used by the single-light readback path
     blend_pipeline: BlendPipeline,
     blend_to_texture_pipeline: BlendToTexturePipeline,
     blur_pipeline: BlurPipeline,
@@ -78,6 +80,17 @@ struct ViewerState {
     
     // Light source intensity
     source_intensity: f32,
+    
+    // Attenuation solvers and the selected one
+    algorithms: Vec<Box<dyn AttenuationAlgorithm>>,
+    algorithm: usize,
+    
+    // Placed lights baked for the decay grid; only the mouse light is swept per frame
+    baked: BakedLighting,
+    
+    // Per-frame buffers reused so relighting does not allocate
+    sources: Vec<(usize, usize, f32)>,
+    attenuation: Vec<f32>,
 }
 
 impl ViewerState {
@@ -123,6 +136,7 @@ impl ViewerState {
         let decay_flat = vec![config.base_decay; grid_w * grid_h];
         let wall_flat = vec![false; grid_w * grid_h];
         let pixel_buffer = vec![0u8; grid_w * grid_h * 4];
+        let baked = BakedLighting::new(Sweeping::new(), Combine::Max, &decay_flat, grid_w, grid_h);
         
         let current_color = config.light_color;
         let current_mode = config.normalization_mode;
@@ -152,6 +166,11 @@ impl ViewerState {
             placed_lights: Vec::new(),
             mouse_light_enabled: true,
             source_intensity: 1.0,
+            algorithms: super::algorithms(),
+            algorithm: SWEEPING,
+            baked,
+            sources: Vec::new(),
+            attenuation: vec![0.0; grid_w * grid_h],
         })
     }
     
@@ -172,11 +191,13 @@ impl ViewerState {
         } else {
             self.config.base_decay
         };
+        self.baked.set_decay(x, y, self.decay_flat[idx]);
     }
     
     fn clear_walls(&mut self) {
         self.wall_flat.fill(false);
         self.decay_flat.fill(self.config.base_decay);
+        self.baked.set_decay_grid(&self.decay_flat);
     }
     
     fn place_light(&mut self, x: usize, y: usize) {
@@ -273,6 +294,7 @@ impl ViewerState {
                 self.config.base_decay
             };
         }
+        self.baked.set_decay_grid(&self.decay_flat);
     }
     
     fn update_decay_grid(&mut self) {
@@ -281,8 +303,10 @@ impl ViewerState {
                 self.decay_flat[i] = self.config.base_decay;
             }
         }
+        self.baked.set_decay_grid(&self.decay_flat);
     }
     
+    #[allow(dead_code)] // Single-light paths, superseded by render_multi_lights
     fn render_lighting(&mut self, light_x: usize, light_y: usize) {
         let (grid_w, grid_h) = self.config.grid_size;
         
@@ -296,54 +320,22 @@ impl ViewerState {
         }
     }
     
+    #[allow(dead_code)]
     fn render_lighting_bilinear(&mut self, subpixel_x: f32, subpixel_y: f32) {
         let (grid_w, grid_h) = self.config.grid_size;
         
-        let x0 = (subpixel_x.floor() as usize).min(grid_w - 1);
-        let y0 = (subpixel_y.floor() as usize).min(grid_h - 1);
-        let x1 = (x0 + 1).min(grid_w - 1);
-        let y1 = (y0 + 1).min(grid_h - 1);
-        
-        let fx = subpixel_x.fract().max(0.0);
-        let fy = subpixel_y.fract().max(0.0);
-        
-        let w00 = (1.0 - fx) * (1.0 - fy);
-        let w10 = fx * (1.0 - fy);
-        let w01 = (1.0 - fx) * fy;
-        let w11 = fx * fy;
-        
-        let positions = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)];
-        let decay_flat = &self.decay_flat;
-        
-        // Calculate 4 grids in parallel (CPU)
-        let grids: Vec<Vec<f32>> = positions
-            .par_iter()
-            .map(|&(lx, ly)| {
-                let sweeping = Sweeping::new();
-                sweeping.calculate_flat(decay_flat, grid_w, grid_h, lx, ly, 1.0)
-            })
-            .collect();
+        // Single sweep with the light at its fractional position
+        let sweeping = Sweeping::new();
+        let attenuation = sweeping.calculate_subpixel(&self.decay_flat, grid_w, grid_h, subpixel_x, subpixel_y, 1.0);
         
         if self.use_gpu_blend {
-            // GPU path: upload grids and run compute shader
-            self.render_bilinear_gpu(&grids, [w00, w10, w01, w11]);
+            self.render_single_gpu(&attenuation);
         } else {
-            // CPU path: blend on CPU
-            let weights = [w00, w10, w01, w11];
-            let size = grid_w * grid_h;
-            let mut blended = vec![0.0f32; size];
-            
-            for i in 0..size {
-                blended[i] = grids[0][i] * weights[0]
-                           + grids[1][i] * weights[1]
-                           + grids[2][i] * weights[2]
-                           + grids[3][i] * weights[3];
-            }
-            
-            self.render_attenuation_to_buffer(&blended);
+            self.render_attenuation_to_buffer(&attenuation);
         }
     }
     
+    #[allow(dead_code)]
     fn render_bilinear_gpu(&mut self, grids: &[Vec<f32>], weights: [f32; 4]) {
         let (grid_w, grid_h) = self.config.grid_size;
         let color = self.current_color;
@@ -461,6 +453,7 @@ impl ViewerState {
         // No readback needed!
     }
     
+    #[allow(dead_code)]
     fn read_compute_output_to_pixel_buffer(&mut self) {
         let (grid_w, grid_h) = self.config.grid_size;
         let num_pixels = grid_w * grid_h;
@@ -561,28 +554,26 @@ impl ViewerState {
         }
     }
     
-    /// Render multiple light sources, combining with max to avoid over-saturation
-    fn render_multi_lights(&mut self, lights: &[(usize, usize)]) {
+    /// Render the placed lights plus `dynamic` ones, combining with max to avoid over-saturation
+    fn render_multi_lights(&mut self, dynamic: &[(usize, usize)]) {
         let (grid_w, grid_h) = self.config.grid_size;
-        let size = grid_w * grid_h;
-        let decay_flat = &self.decay_flat;
         let source_intensity = self.source_intensity;
-        
-        // Calculate attenuation for each light in parallel
-        let grids: Vec<Vec<f32>> = lights
-            .par_iter()
-            .map(|&(lx, ly)| {
-                let sweeping = Sweeping::new();
-                sweeping.calculate_flat(decay_flat, grid_w, grid_h, lx, ly, source_intensity)
-            })
-            .collect();
-        
-        // Combine all grids using max (prevents over-saturation)
-        let mut combined = vec![0.0f32; size];
-        for grid in &grids {
-            for i in 0..size {
-                combined[i] = combined[i].max(grid[i]);
-            }
+        let with_intensity = |&(lx, ly): &(usize, usize)| (lx, ly, source_intensity);
+        
+        let mut combined = std::mem::take(&mut self.attenuation);
+        if self.algorithm == SWEEPING {
+            // Placed lights are only re-swept when they or the walls change
+            self.sources.clear();
+            self.sources.extend(self.placed_lights.iter().map(with_intensity));
+            self.baked.set_static_lights(&self.sources);
+            self.sources.clear();
+            self.sources.extend(dynamic.iter().map(with_intensity));
+            self.baked.composite_into(&self.sources, &mut combined);
+        } else {
+            // All sources are seeded into one grid and solved once (max-combined)
+            self.sources.clear();
+            self.sources.extend(self.placed_lights.iter().chain(dynamic).map(with_intensity));
+            self.algorithms[self.algorithm].calculate_into(&self.decay_flat, grid_w, grid_h, &self.sources, &mut combined);
         }
         
         // Render the combined result
@@ -591,6 +582,7 @@ impl ViewerState {
         } else {
             self.render_attenuation_to_buffer(&combined);
         }
+        self.attenuation = combined;
     }
     
     /// Render with no light sources (just walls visible)
@@ -612,8 +604,8 @@ impl ViewerState {
         let (grid_w, grid_h) = self.config.grid_size;
         let window_size = self.gpu_ctx.size;
         
-        // Collect all light sources
-        let mut all_lights: Vec<(usize, usize)> = self.placed_lights.clone();
+        // Lights that move with the mouse; placed lights are baked
+        let mut dynamic_lights: Vec<(usize, usize)> = Vec::new();
         
         if let Some((mx, my)) = self.mouse_pos {
             // Convert window coords to grid coords
@@ -627,25 +619,23 @@ impl ViewerState {
             let grid_y = (subpixel_y as usize).min(grid_h - 1);
             
             // Handle wall toggling
-            if self.left_mouse_down {
-                if self.last_wall_pos != Some((grid_x, grid_y)) {
-                    self.toggle_wall(grid_x, grid_y);
-                    self.last_wall_pos = Some((grid_x, grid_y));
-                }
+            if self.left_mouse_down && self.last_wall_pos != Some((grid_x, grid_y)) {
+                self.toggle_wall(grid_x, grid_y);
+                self.last_wall_pos = Some((grid_x, grid_y));
             }
             
             // Add mouse light if enabled
             if self.mouse_light_enabled {
-                all_lights.push((grid_x, grid_y));
+                dynamic_lights.push((grid_x, grid_y));
             }
-        } else if self.mouse_light_enabled && all_lights.is_empty() {
+        } else if self.mouse_light_enabled && self.placed_lights.is_empty() {
             // No mouse position, no placed lights, render from center
-            all_lights.push((grid_w / 2, grid_h / 2));
+            dynamic_lights.push((grid_w / 2, grid_h / 2));
         }
         
         // Render lighting with all light sources
-        if !all_lights.is_empty() {
-            self.render_multi_lights(&all_lights);
+        if !self.placed_lights.is_empty() || !dynamic_lights.is_empty() {
+            self.render_multi_lights(&dynamic_lights);
         } else {
             // No lights at all - render a dark scene
             self.render_no_lights();
@@ -724,6 +714,7 @@ impl ApplicationHandler for GpuViewerApp {
                 println!("  L          - Cycle blur: OFF/Light/Medium/Heavy");
                 println!("  [/]        - Adjust source intensity");
                 println!("  V          - Generate random cave");
+                println!("  A          - Cycle algorithm");
                 println!("  C          - Clear walls");
                 println!("  ESC        - Exit");
                 println!();
@@ -895,6 +886,11 @@ impl ApplicationHandler for GpuViewerApp {
                         state.clear_lights();
                     }
                     
+                    KeyCode::KeyA => {
+                        state.algorithm = (state.algorithm + 1) % state.algorithms.len();
+                        println!("Algorithm: {}", state.algorithms[state.algorithm].name());
+                    }
+                    
                     KeyCode::BracketLeft => {
                         state.source_intensity = (state.source_intensity - 0.1).max(0.1);
                         println!("Source intensity: {:.1}", state.source_intensity);
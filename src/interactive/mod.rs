@@ -1,7 +1,29 @@
 //! Interactive visualization module for real-time lighting testing
 
+#[cfg(feature = "viewer")]
 mod viewer;
+#[cfg(feature = "gpu")]
 pub mod gpu_viewer;
 
+#[cfg(feature = "viewer")]
 pub use viewer::{InteractiveViewer, ViewerConfig};
+#[cfg(feature = "gpu")]
 pub use gpu_viewer::{run_gpu_viewer, GpuViewerConfig};
+
+#[cfg(any(feature = "viewer", feature = "gpu"))]
+use lighting_test::attenuation::{AttenuationAlgorithm, BestPath, FloodFill, Raycast, Sweeping};
+
+/// Index of [`Sweeping`] in [`algorithms`], the only solver the viewers cache or bake
+#[cfg(any(feature = "viewer", feature = "gpu"))]
+const SWEEPING: usize = 0;
+
+/// Solvers the viewers cycle through with the `A` key (sweeping first)
+#[cfg(any(feature = "viewer", feature = "gpu"))]
+fn algorithms() -> Vec<Box<dyn AttenuationAlgorithm>> {
+    vec![
+        Box::new(Sweeping::new()),
+        Box::new(FloodFill::new()),
+        Box::new(Raycast::new()),
+        Box::new(BestPath::new()),
+    ]
+}
@@ -1,9 +1,10 @@
 //! Interactive light viewer - mouse controls light position in real-time
 
 use minifb::{Key, Window, WindowOptions, MouseMode, MouseButton};
-use rayon::prelude::*;
-use crate::attenuation::Sweeping;
-use crate::render::{NormalizationMode, to_byte};
+use lighting_test::attenuation::{AttenuationAlgorithm, AttenuationCache, Sweeping};
+use lighting_test::render::{NormalizationMode, to_byte};
+
+use super::SWEEPING;
 
 /// Configuration for the interactive viewer
 #[derive(Clone)]
@@ -35,6 +36,9 @@ impl Default for ViewerConfig {
     }
 }
 
+/// Memory for cached attenuation grids (~100 grids of 100x100 cells)
+const CACHE_BUDGET_BYTES: usize = 4 << 20;
+
 /// Interactive viewer for testing lighting algorithms
 pub struct InteractiveViewer {
     config: ViewerConfig,
@@ -42,6 +46,11 @@ pub struct InteractiveViewer {
     wall_flat: Vec<bool>,
     window: Window,
     buffer: Vec<u32>,
+    algorithms: Vec<Box<dyn AttenuationAlgorithm>>,
+    algorithm: usize,
+    /// Recent sweeping results, so a light moving among a few cells is not re-swept
+    cache: AttenuationCache,
+    blended: Vec<f32>,
 }
 
 impl InteractiveViewer {
@@ -65,6 +74,7 @@ impl InteractiveViewer {
         let decay_flat = vec![config.base_decay; grid_w * grid_h];
         let wall_flat = vec![false; grid_w * grid_h];
         let buffer = vec![0u32; window_w * window_h];
+        let cache = AttenuationCache::new(Sweeping::new(), &decay_flat, grid_w, grid_h, CACHE_BUDGET_BYTES);
         
         Ok(Self {
             config,
@@ -72,6 +82,10 @@ impl InteractiveViewer {
             wall_flat,
             window,
             buffer,
+            algorithms: super::algorithms(),
+            algorithm: SWEEPING,
+            cache,
+            blended: vec![0.0; grid_w * grid_h],
         })
     }
     
@@ -98,6 +112,7 @@ impl InteractiveViewer {
         println!("  R/G/B/Y/W  - Color: Red/Green/Blue/Yellow/White");
         println!("  +/-        - Adjust decay rate");
         println!("  T          - Toggle subpixel blending ON/OFF");
+        println!("  A          - Cycle algorithm (subpixel needs sweeping)");
         println!("  C          - Clear walls");
         println!("  ESC        - Exit");
         println!();
@@ -162,13 +177,19 @@ impl InteractiveViewer {
             // Toggle bilinear blending
             if self.window.is_key_pressed(Key::T, minifb::KeyRepeat::No) {
                 subpixel_enabled = !subpixel_enabled;
-                if subpixel_enabled {
+                if subpixel_enabled && self.algorithm == SWEEPING {
                     println!("Subpixel blending: ON (smooth movement)");
                 } else {
                     println!("Subpixel blending: OFF (snappy grid)");
                 }
             }
             
+            // Cycle attenuation algorithm
+            if self.window.is_key_pressed(Key::A, minifb::KeyRepeat::No) {
+                self.algorithm = (self.algorithm + 1) % self.algorithms.len();
+                println!("Algorithm: {}", self.algorithms[self.algorithm].name());
+            }
+            
             // Handle mouse input for walls
             if let Some((mx, my)) = self.window.get_mouse_pos(MouseMode::Discard) {
                 let grid_x = (mx as usize / scale).min(grid_w - 1);
@@ -200,8 +221,8 @@ impl InteractiveViewer {
                 let subpixel_x = mx / scale as f32;
                 let subpixel_y = my / scale as f32;
                 
-                if subpixel_enabled {
-                    // 4-grid bilinear (smooth movement, fast with rayon)
+                if subpixel_enabled && self.algorithm == SWEEPING {
+                    // 4-grid bilinear from cached corner grids (smooth movement)
                     self.render_lighting_bilinear(subpixel_x, subpixel_y, current_color, current_mode);
                 } else {
                     // Snap to nearest cell
@@ -216,6 +237,14 @@ impl InteractiveViewer {
                 .map_err(|e| e.to_string())?;
         }
         
+        let stats = self.cache.stats();
+        println!(
+            "Attenuation cache: {:.1}% hits ({} hits, {} sweeps, {} evicted)",
+            stats.hit_rate() * 100.0,
+            stats.hits,
+            stats.misses,
+            stats.evictions
+        );
         Ok(())
     }
     
@@ -229,12 +258,14 @@ impl InteractiveViewer {
         } else {
             self.config.base_decay
         };
+        self.cache.set_decay(x, y, self.decay_flat[idx]);
     }
     
     /// Clear all walls
     fn clear_walls(&mut self) {
         self.wall_flat.fill(false);
         self.decay_flat.fill(self.config.base_decay);
+        self.cache.set_decay_grid(&self.decay_flat);
     }
     
     /// Update decay grid after base_decay change (preserves walls)
@@ -244,68 +275,60 @@ impl InteractiveViewer {
                 self.decay_flat[i] = self.config.base_decay;
             }
         }
+        self.cache.set_decay_grid(&self.decay_flat);
     }
     
     /// Render lighting from the given integer position (fully flat pipeline)
     fn render_lighting(&mut self, light_x: usize, light_y: usize, color: (f32, f32, f32), mode: NormalizationMode) {
         let (grid_w, grid_h) = self.config.grid_size;
         
-        // Calculate attenuation using sweeping algorithm
-        let sweeping = Sweeping::new();
-        let attenuation = sweeping.calculate_flat(&self.decay_flat, grid_w, grid_h, light_x, light_y, 1.0);
+        // Calculate attenuation using the selected algorithm (sweeping results are cached)
+        let mut blended = std::mem::take(&mut self.blended);
+        if self.algorithm == SWEEPING {
+            blended.copy_from_slice(self.cache.get(light_x, light_y, 1.0));
+        } else {
+            let sources = [(light_x, light_y, 1.0)];
+            self.algorithms[self.algorithm].calculate_into(&self.decay_flat, grid_w, grid_h, &sources, &mut blended);
+        }
         
         // Render directly to buffer (fused color + normalize + write)
-        self.render_flat_to_buffer(&attenuation, color, mode);
+        self.render_flat_to_buffer(&blended, color, mode);
+        self.blended = blended;
     }
     
-    /// Render lighting with bilinear blending for subpixel positions
-    /// Calculates light from 4 neighboring cells and blends based on fractional position
-    /// Uses rayon thread pool and flat memory layout for best performance
+    /// Render lighting from a subpixel position
+    /// Blends the four surrounding cells' grids, which stay cached while the light
+    /// moves within a cell, so only entering new cells costs sweeps
     fn render_lighting_bilinear(&mut self, subpixel_x: f32, subpixel_y: f32, color: (f32, f32, f32), mode: NormalizationMode) {
         let (grid_w, grid_h) = self.config.grid_size;
-        let size = grid_w * grid_h;
         
-        // Get the 4 corner cell positions
-        let x0 = (subpixel_x.floor() as usize).min(grid_w - 1);
-        let y0 = (subpixel_y.floor() as usize).min(grid_h - 1);
+        let x0 = (subpixel_x.max(0.0).floor() as usize).min(grid_w - 1);
+        let y0 = (subpixel_y.max(0.0).floor() as usize).min(grid_h - 1);
         let x1 = (x0 + 1).min(grid_w - 1);
         let y1 = (y0 + 1).min(grid_h - 1);
+        let tx = (subpixel_x - x0 as f32).clamp(0.0, 1.0);
+        let ty = (subpixel_y - y0 as f32).clamp(0.0, 1.0);
+        let corners = [
+            (x0, y0, (1.0 - tx) * (1.0 - ty)),
+            (x1, y0, tx * (1.0 - ty)),
+            (x0, y1, (1.0 - tx) * ty),
+            (x1, y1, tx * ty),
+        ];
         
-        // Fractional part determines blend weights
-        let fx = subpixel_x.fract().max(0.0);
-        let fy = subpixel_y.fract().max(0.0);
-        
-        // Bilinear weights for each corner
-        let w00 = (1.0 - fx) * (1.0 - fy);  // top-left
-        let w10 = fx * (1.0 - fy);          // top-right
-        let w01 = (1.0 - fx) * fy;          // bottom-left
-        let w11 = fx * fy;                  // bottom-right
-        
-        // Calculate all 4 corners in parallel using rayon
-        let positions = [(x0, y0), (x1, y0), (x0, y1), (x1, y1)];
-        let decay_flat = &self.decay_flat;
-        
-        let grids: Vec<Vec<f32>> = positions
-            .par_iter()
-            .map(|&(lx, ly)| {
-                let sweeping = Sweeping::new();
-                sweeping.calculate_flat(decay_flat, grid_w, grid_h, lx, ly, 1.0)
-            })
-            .collect();
-        
-        // Blend all 4 grids into a single flat buffer
-        let weights = [w00, w10, w01, w11];
-        let mut blended = vec![0.0f32; size];
-        
-        for i in 0..size {
-            blended[i] = grids[0][i] * weights[0] 
-                       + grids[1][i] * weights[1] 
-                       + grids[2][i] * weights[2] 
-                       + grids[3][i] * weights[3];
+        let mut blended = std::mem::take(&mut self.blended);
+        blended.fill(0.0);
+        for (cx, cy, weight) in corners {
+            if weight == 0.0 {
+                continue;
+            }
+            for (b, g) in blended.iter_mut().zip(self.cache.get(cx, cy, 1.0)) {
+                *b += weight * g;
+            }
         }
         
         // Render directly to buffer (fused color + normalize + write)
         self.render_flat_to_buffer(&blended, color, mode);
+        self.blended = blended;
     }
     
     /// Render flat attenuation directly to pixel buffer
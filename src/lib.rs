@@ -0,0 +1,25 @@
+//! lightgrid2d: fast 2D light propagation through decay grids.
+//!
+//! The library is organized in layers:
+//! 1. [`attenuation`] – pure geometry/physics calculation of light propagation
+//! 2. [`color`] – applies colors to attenuation grids, blends multiple lights
+//! 3. [`render`] – normalization and output (PPM export, display buffers)
+//!
+//! The interactive viewers and benchmarks live in the `lighting_test` binary.
+
+pub mod attenuation;
+pub mod color;
+pub mod render;
+
+#[cfg(test)]
+mod tests;
+
+// Run the README's code examples as doctests
+#[cfg(doctest)]
+#[doc = include_str!("../README.md")]
+pub struct ReadmeDoctests;
+
+// Re-export public API
+pub use attenuation::{Sweeping, LightError, flatten_grid, attenuation_to_string};
+pub use color::{RGBA, ColoredLight, apply_light_color, apply_light_color_rgb, apply_sky_light, blend_lights, rgba_grid_to_string};
+pub use render::{save_ppm, save_ppm_with_walls, normalize_grid, normalize_grid_osb, normalize_grid_perceptual, NormalizationMode};
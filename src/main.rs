@@ -1,17 +1,21 @@
-mod attenuation;
-mod color;
+//! Interactive viewers and benchmarks for the lighting library.
+
+// Pipelines hold some GPU resources purely to keep them alive, and expose
+// single-grid helpers that not every viewer path uses.
+#[cfg(feature = "gpu")]
+#[allow(dead_code)]
 mod gpu;
 mod interactive;
-mod render;
-
-#[cfg(test)]
-mod tests;
 
-// Re-export public API
-pub use attenuation::{Sweeping, flatten_grid, attenuation_to_string};
-pub use color::{RGBA, ColoredLight, apply_light_color, blend_lights, rgba_grid_to_string};
-pub use render::{save_ppm, save_ppm_with_walls, normalize_grid, normalize_grid_osb, normalize_grid_perceptual, NormalizationMode};
-pub use interactive::{InteractiveViewer, ViewerConfig, run_gpu_viewer, GpuViewerConfig};
+use lighting_test::attenuation::{
+    AttenuationAlgorithm, AttenuationCache, BakedLighting, BestPath, Combine, Exponential, FloodFill, Raycast,
+    SweepWorkspace, TransmittanceGrid, compare_grids,
+};
+use lighting_test::{Sweeping, flatten_grid};
+#[cfg(feature = "viewer")]
+use interactive::{InteractiveViewer, ViewerConfig};
+#[cfg(feature = "gpu")]
+use interactive::{run_gpu_viewer, GpuViewerConfig};
 
 fn main() {
     // Check for command line arguments
@@ -69,6 +73,137 @@ fn run_benchmark() {
         println!();
     }
     
+    // Benchmark a dim torch in a large world (bounded computation window)
+    println!("=== Torch in a Large World ===");
+    println!("(intensity 0.3, decay 0.1, sweep window vs full grid)\n");
+    
+    let (width, height) = (1000, 1000);
+    let decay_flat: Vec<f32> = vec![decay_value; width * height];
+    let iterations_large = 5;
+    
+    for (label, sweeping) in [
+        ("Full grid", Sweeping { epsilon: 0.0, ..Sweeping::new() }),
+        ("Windowed", Sweeping::new()),
+    ] {
+        let start = Instant::now();
+        for _ in 0..iterations_large {
+            let _ = sweeping.calculate_flat(&decay_flat, width, height, width / 2, height / 2, 0.3);
+        }
+        let avg_ms = start.elapsed().as_secs_f64() * 1000.0 / iterations_large as f64;
+        println!("  {:<10} {:.3} ms/iter", label, avg_ms);
+    }
+    println!();
+
+    // Memory-bound case: every cell of a very large grid is swept
+    println!("=== Large Grids ===");
+    println!("(open air decay 0.01, full grid, epsilon 0, 8-neighbour kernel)\n");
+
+    let full = Sweeping { epsilon: 0.0, ..Sweeping::new() };
+    for size in [1024, 2048, 4096] {
+        // Low decay keeps far cells out of the (slow) subnormal float range
+        let decay_flat: Vec<f32> = vec![0.01; size * size];
+        let iterations_huge = (4096 / size).max(2);
+        let start = Instant::now();
+        for _ in 0..iterations_huge {
+            let _ = full.calculate_flat(&decay_flat, size, size, size / 2, size / 2, 1.0);
+        }
+        let avg_ms = start.elapsed().as_secs_f64() * 1000.0 / iterations_huge as f64;
+        println!("  {0}x{0}  {1:9.3} ms/iter", size, avg_ms);
+    }
+    println!();
+
+    // Forward/reverse passes and their column-strip wavefronts on pools of each size
+    println!("=== Thread Scaling ===");
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+    println!("(2048x2048, open air decay 0.01, full grid, {} core(s) available)\n", cores);
+
+    let size = 2048;
+    let decay_flat: Vec<f32> = vec![0.01; size * size];
+    let mut workspace = SweepWorkspace::new(Sweeping { epsilon: 0.0, ..Sweeping::new() });
+    let mut out = vec![0.0f32; size * size];
+    let mut single_ms = 0.0;
+    for threads in [1, 2, 4, 8] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        let avg_ms = pool.install(|| {
+            // Warm-up sizes the workspace and wakes the pool
+            workspace.calculate_into(&decay_flat, size, size, &[(size / 2, size / 2, 1.0)], &mut out);
+            let start = Instant::now();
+            for _ in 0..3 {
+                workspace.calculate_into(&decay_flat, size, size, &[(size / 2, size / 2, 1.0)], &mut out);
+            }
+            start.elapsed().as_secs_f64() * 1000.0 / 3.0
+        });
+        if threads == 1 {
+            single_ms = avg_ms;
+        }
+        println!("  {} thread(s)  {:9.3} ms/iter  {:.2}x", threads, avg_ms, single_ms / avg_ms);
+    }
+    println!();
+
+    // Static world: step factors computed once instead of in every sweep
+    println!("=== Transmittance Grid ===");
+    println!("(1024x1024, open air, full grid, factors precomputed vs from decay)\n");
+
+    let size = 1024;
+    let decay_flat: Vec<f32> = vec![0.01; size * size];
+    let lights = [(size / 2, size / 2, 1.0)];
+    let linear = Sweeping { epsilon: 0.0, ..Sweeping::new() };
+    let exponential = Sweeping { epsilon: 0.0, ..Sweeping::with_model(Exponential) };
+    let linear_grid = TransmittanceGrid::new(&linear, &decay_flat, size, size);
+    let exponential_grid = TransmittanceGrid::new(&exponential, &decay_flat, size, size);
+    let time = |f: &dyn Fn() -> Vec<f32>| {
+        let start = Instant::now();
+        for _ in 0..4 {
+            let _ = f();
+        }
+        start.elapsed().as_secs_f64() * 1000.0 / 4.0
+    };
+    println!(
+        "  {:<12} {:8.3} ms/iter from decay   {:8.3} ms/iter precomputed",
+        "Linear",
+        time(&|| linear.calculate_many(&decay_flat, size, size, &lights)),
+        time(&|| linear.calculate_with(&linear_grid, &lights)),
+    );
+    println!(
+        "  {:<12} {:8.3} ms/iter from decay   {:8.3} ms/iter precomputed",
+        "Exponential",
+        time(&|| exponential.calculate_many(&decay_flat, size, size, &lights)),
+        time(&|| exponential.calculate_with(&exponential_grid, &lights)),
+    );
+    println!();
+
+    // Static torches swept once; each frame only sweeps the moving light
+    println!("=== Baked Static Lights ===");
+    println!("(512x512, 64 torches baked, 1 moving light per frame)\n");
+
+    let size = 512;
+    let decay_flat: Vec<f32> = vec![0.05; size * size];
+    let torches: Vec<(usize, usize, f32)> = (0..64).map(|i| (32 + 64 * (i % 8), 32 + 64 * (i / 8), 1.0)).collect();
+    let sweeping = Sweeping::new();
+    let mut baked = BakedLighting::new(sweeping.clone(), Combine::Max, &decay_flat, size, size);
+    baked.set_static_lights(&torches);
+    let mut out = vec![0.0f32; size * size];
+    let frames = 20;
+
+    let mut all = torches.clone();
+    all.push((0, 0, 1.0));
+    let start = Instant::now();
+    for frame in 0..frames {
+        *all.last_mut().unwrap() = (100 + frame, 200, 1.0);
+        let _ = sweeping.calculate_many(&decay_flat, size, size, &all);
+    }
+    let every_light = start.elapsed().as_secs_f64() * 1000.0 / frames as f64;
+
+    baked.bake();
+    let start = Instant::now();
+    for frame in 0..frames {
+        baked.composite_into(&[(100 + frame, 200, 1.0)], &mut out);
+    }
+    let composited = start.elapsed().as_secs_f64() * 1000.0 / frames as f64;
+    println!("  All lights per frame:   {:8.3} ms/frame", every_light);
+    println!("  Baked + dynamic light:  {:8.3} ms/frame", composited);
+    println!();
+
     // Benchmark 4-grid bilinear scenario (the real use case)
     println!("=== 4-Grid Bilinear Scenario ===");
     println!("(Simulates subpixel light movement)\n");
@@ -95,11 +230,74 @@ fn run_benchmark() {
     println!("  Time: {:.3} ms/iter", avg_parallel_ms);
     println!();
     
+    // Single sweep with the light at its fractional position
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let _ = Sweeping::new().calculate_subpixel(&decay_flat, width, height, 49.5, 49.5, 1.0);
+    }
+    let avg_subpixel_ms = start.elapsed().as_secs_f64() * 1000.0 / iterations as f64;
+    
+    println!("Grid size: {}x{}, calculate_subpixel (single sweep)", width, height);
+    println!("-----------------------");
+    println!("  Time: {:.3} ms/iter", avg_subpixel_ms);
+    println!();
+
+    // Light jittering inside one cell: the four corner grids come from the cache
+    let mut cache = AttenuationCache::new(Sweeping::new(), &decay_flat, width, height, 1 << 20);
+    let mut blended = vec![0.0f32; width * height];
+    let start = Instant::now();
+    for _ in 0..iterations {
+        blended.fill(0.0);
+        for &(x, y) in &positions {
+            for (b, g) in blended.iter_mut().zip(cache.get(x, y, 1.0)) {
+                *b += 0.25 * g;
+            }
+        }
+    }
+    let avg_cached_ms = start.elapsed().as_secs_f64() * 1000.0 / iterations as f64;
+
+    println!("Grid size: {}x{}, 4 grids from AttenuationCache", width, height);
+    println!("-----------------------");
+    println!("  Time: {:.3} ms/iter ({:.0}% hits)", avg_cached_ms, cache.stats().hit_rate() * 100.0);
+    println!();
+
     // FPS estimate
-    let fps_parallel = 1000.0 / avg_parallel_ms;
-    println!("Estimated max FPS (lighting only): {:.1} FPS", fps_parallel);
+    let fps_subpixel = 1000.0 / avg_subpixel_ms;
+    println!("Estimated max FPS (lighting only): {:.1} FPS", fps_subpixel);
+    println!();
+
+    // Compare every solver on the same scattered-wall map
+    println!("=== Algorithm Comparison ===");
+    println!("(100x100, scattered walls, error vs exact best path)\n");
+
+    let decay_flat: Vec<f32> = (0..width * height)
+        .map(|i| if (i % width * 7 + i / width * 13) % 11 == 0 { 0.6 } else { decay_value })
+        .collect();
+    let sources = [(width / 2, height / 2, 1.0)];
+    let exact = BestPath::new().calculate(&decay_flat, width, height, &sources);
+
+    let algorithms: Vec<Box<dyn AttenuationAlgorithm>> = vec![
+        Box::new(Sweeping::new()),
+        Box::new(FloodFill::new()),
+        Box::new(Raycast::new()),
+        Box::new(BestPath::new()),
+    ];
+    let mut out = vec![0.0f32; width * height];
+    for algorithm in &algorithms {
+        let start = Instant::now();
+        for _ in 0..iterations {
+            algorithm.calculate_into(&decay_flat, width, height, &sources, &mut out);
+        }
+        let avg_ms = start.elapsed().as_secs_f64() * 1000.0 / iterations as f64;
+        let stats = compare_grids(&out, &exact, width);
+        println!(
+            "  {:<11} {:8.3} ms/iter   max err {:.4}   mean err {:.5}",
+            algorithm.name(), avg_ms, stats.max_error, stats.mean_error
+        );
+    }
 }
 
+#[cfg(feature = "viewer")]
 fn run_interactive() {
     let config = ViewerConfig::default();
 
@@ -115,6 +313,12 @@ fn run_interactive() {
     }
 }
 
+#[cfg(not(feature = "viewer"))]
+fn run_interactive() {
+    eprintln!("Built without the `viewer` feature; rebuild with --features viewer");
+}
+
+#[cfg(feature = "gpu")]
 fn run_gpu_interactive() {
     let config = GpuViewerConfig::default();
     
@@ -123,6 +327,17 @@ fn run_gpu_interactive() {
     }
 }
 
+#[cfg(not(feature = "gpu"))]
+fn run_gpu_interactive() {
+    eprintln!("Built without the `gpu` feature; rebuild with --features gpu");
+}
+
+#[cfg(not(feature = "gpu"))]
+fn run_blend_benchmark() {
+    eprintln!("Built without the `gpu` feature; rebuild with --features gpu");
+}
+
+#[cfg(feature = "gpu")]
 fn run_blend_benchmark() {
     use std::time::Instant;
     use rayon::prelude::*;
@@ -144,6 +359,7 @@ fn run_blend_benchmark() {
     // Create a hidden window for GPU context
     println!("Initializing GPU...");
     let event_loop = EventLoop::new().expect("Failed to create event loop");
+    #[allow(deprecated)] // No ApplicationHandler needed for a one-shot hidden window
     let window = Arc::new(
         event_loop
             .create_window(
@@ -360,4 +576,12 @@ fn run_blend_benchmark() {
     
     println!("Note: 'GPU to texture' writes directly to GPU texture,");
     println!("      avoiding CPU readback. This is used for display.");
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_main() {
+        super::main();
+    }
+}
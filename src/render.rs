@@ -22,7 +22,7 @@ pub fn to_byte(value: f32) -> u8 {
 }
 
 /// Normalize an RGBA grid using the specified mode
-pub fn normalize_grid_with_mode(grid: &Vec<Vec<RGBA>>, mode: NormalizationMode) -> Vec<Vec<RGBA>> {
+pub fn normalize_grid_with_mode(grid: &[Vec<RGBA>], mode: NormalizationMode) -> Vec<Vec<RGBA>> {
     match mode {
         NormalizationMode::Standard => normalize_grid(grid),
         NormalizationMode::BrightnessLimit(limit) => normalize_grid_osb(grid, limit),
@@ -31,21 +31,20 @@ pub fn normalize_grid_with_mode(grid: &Vec<Vec<RGBA>>, mode: NormalizationMode)
 }
 
 /// Standard normalization: scale all values by global maximum
-pub fn normalize_grid(grid: &Vec<Vec<RGBA>>) -> Vec<Vec<RGBA>> {
+pub fn normalize_grid(grid: &[Vec<RGBA>]) -> Vec<Vec<RGBA>> {
     let width = grid.len();
     let height = if width > 0 { grid[0].len() } else { 0 };
     
     // Find global maximum across all channels
     let mut max_val = 0.0f32;
-    for x in 0..width {
-        for y in 0..height {
-            let pixel = &grid[x][y];
+    for column in grid {
+        for pixel in column {
             max_val = max_val.max(pixel.r).max(pixel.g).max(pixel.b);
         }
     }
     
     if max_val <= 0.0 {
-        return grid.clone();
+        return grid.to_vec();
     }
     
     // Scale all values
@@ -68,7 +67,7 @@ pub fn normalize_grid(grid: &Vec<Vec<RGBA>>) -> Vec<Vec<RGBA>> {
 }
 
 /// OpenStarbound-style normalization: per-pixel brightness limiting
-pub fn normalize_grid_osb(grid: &Vec<Vec<RGBA>>, limit: f32) -> Vec<Vec<RGBA>> {
+pub fn normalize_grid_osb(grid: &[Vec<RGBA>], limit: f32) -> Vec<Vec<RGBA>> {
     let width = grid.len();
     let height = if width > 0 { grid[0].len() } else { 0 };
     
@@ -102,7 +101,7 @@ pub fn normalize_grid_osb(grid: &Vec<Vec<RGBA>>, limit: f32) -> Vec<Vec<RGBA>> {
 }
 
 /// Perceptual luminance-based normalization
-pub fn normalize_grid_perceptual(grid: &Vec<Vec<RGBA>>, target_luminance: f32) -> Vec<Vec<RGBA>> {
+pub fn normalize_grid_perceptual(grid: &[Vec<RGBA>], target_luminance: f32) -> Vec<Vec<RGBA>> {
     let width = grid.len();
     let height = if width > 0 { grid[0].len() } else { 0 };
     
@@ -145,14 +144,14 @@ pub fn normalize_grid_perceptual(grid: &Vec<Vec<RGBA>>, target_luminance: f32) -
 }
 
 /// Save an RGBA grid to a PPM file
-pub fn save_ppm(grid: &Vec<Vec<RGBA>>, filename: &str, scale: usize) -> io::Result<()> {
+pub fn save_ppm(grid: &[Vec<RGBA>], filename: &str, scale: usize) -> io::Result<()> {
     save_ppm_with_walls(grid, None, 0.5, filename, scale)
 }
 
 /// Save an RGBA grid to a PPM file, optionally showing walls
 pub fn save_ppm_with_walls(
-    grid: &Vec<Vec<RGBA>>,
-    decay_grid: Option<&Vec<Vec<f32>>>,
+    grid: &[Vec<RGBA>],
+    decay_grid: Option<&[Vec<f32>]>,
     wall_threshold: f32,
     filename: &str,
     scale: usize,
@@ -197,7 +196,7 @@ pub fn save_ppm_with_walls(
 }
 
 /// Save a pre-normalized RGBA grid to a PPM file
-pub fn save_ppm_normalized(grid: &Vec<Vec<RGBA>>, filename: &str, scale: usize) -> io::Result<()> {
+pub fn save_ppm_normalized(grid: &[Vec<RGBA>], filename: &str, scale: usize) -> io::Result<()> {
     let width = grid.len();
     let height = if width > 0 { grid[0].len() } else { 0 };
     
@@ -0,0 +1,127 @@
+//! Heap allocation checks for the allocation-free relighting paths.
+//!
+//! The counting allocator is process-wide, so it lives in its own test binary
+//! and only counts allocations on the thread that opted in. Rayon pool threads
+//! allocate lazily on their own (e.g. when they first steal work); those are not
+//! the sweep's allocations and are never counted.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use lighting_test::attenuation::{SweepWorkspace, Sweeping};
+
+/// Counts heap allocations made on threads that opted in via `TRACK_ALLOCS`
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static TRACK_ALLOCS: Cell<bool> = const { Cell::new(false) };
+}
+
+fn note_allocation() {
+    if TRACK_ALLOCS.try_with(Cell::get).unwrap_or(false) {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        note_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        note_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Allocations `f` makes on the calling thread
+fn count_allocs(f: impl FnOnce()) -> usize {
+    TRACK_ALLOCS.with(|t| t.set(true));
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    TRACK_ALLOCS.with(|t| t.set(false));
+    count
+}
+
+/// Runs `f` on a dedicated pool of `threads` workers
+#[cfg(feature = "parallel")]
+fn on_pool<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+    rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap().install(f)
+}
+
+#[cfg(not(feature = "parallel"))]
+fn on_pool<R>(_threads: usize, f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// Same deterministic pattern as the library tests: scattered walls in open air
+fn decay_grid(width: usize, height: usize) -> Vec<f32> {
+    (0..width * height).map(|i| if i % 7 == 3 { 0.6 } else { 0.05 + 0.01 * (i % 11) as f32 }).collect()
+}
+
+#[test]
+fn test_workspace_no_allocations_after_warmup() {
+    let (width, height) = (128, 96);
+    let decay = decay_grid(width, height);
+    let mut workspace = SweepWorkspace::new(Sweeping::new());
+    let mut out = vec![0.0; width * height];
+    let mut lights = vec![(0, 0, 1.0); 3];
+
+    // One worker, so every sweep runs on the counted thread
+    let allocations = on_pool(1, || {
+        // Warm-up frame sizes the scratch buffers
+        workspace.calculate_into(&decay, width, height, &lights, &mut out);
+        count_allocs(|| {
+            for frame in 0..60 {
+                // Lights move every frame, as in a game loop
+                lights[0] = (frame * 2, frame, 1.0);
+                lights[1] = (width - 1 - frame, height / 2, 0.7);
+                lights[2] = (width / 2, height - 1 - frame, 0.9);
+                workspace.calculate_into(&decay, width, height, &lights, &mut out);
+            }
+        })
+    });
+
+    assert_eq!(allocations, 0, "calculate_into allocated after warm-up");
+    assert_eq!(out, Sweeping::new().calculate_many(&decay, width, height, &lights));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_workspace_wavefront_no_allocations_after_warmup() {
+    // Large enough, and with an unbounded window, so both passes sweep as
+    // multi-strip wavefronts on a 4-thread pool
+    let (width, height) = (1024, 1024);
+    let decay = decay_grid(width, height);
+    let sweeping = Sweeping { epsilon: 0.0, ..Sweeping::new() };
+    let mut workspace = SweepWorkspace::new(sweeping.clone());
+    let mut out = vec![0.0; width * height];
+    let mut lights = [(0, 0, 1.0)];
+
+    // Counts the calling worker, which runs the forward pass and forks the
+    // other workers, so strip or job allocations of that pass show up here
+    let allocations = on_pool(4, || {
+        workspace.calculate_into(&decay, width, height, &lights, &mut out);
+        count_allocs(|| {
+            for frame in 0..4 {
+                lights[0] = (200 + 100 * frame, 600, 1.0);
+                workspace.calculate_into(&decay, width, height, &lights, &mut out);
+            }
+        })
+    });
+
+    assert_eq!(allocations, 0, "wavefront sweeps allocated after warm-up");
+    assert_eq!(out, sweeping.calculate_many(&decay, width, height, &lights));
+}